@@ -0,0 +1 @@
+DROP TABLE service;
\ No newline at end of file
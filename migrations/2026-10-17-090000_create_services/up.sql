@@ -0,0 +1,4 @@
+CREATE TABLE service (
+  suffix VARCHAR(40) PRIMARY KEY,
+  name VARCHAR(40) NOT NULL
+);
\ No newline at end of file
@@ -0,0 +1,7 @@
+CREATE TABLE blob (
+  id SERIAL PRIMARY KEY,
+  created TIMESTAMPTZ NOT NULL,
+  name VARCHAR(256) NOT NULL,
+  content_type VARCHAR(256) NOT NULL,
+  content BYTEA NOT NULL
+);
@@ -0,0 +1,3 @@
+DROP TRIGGER stub_changed ON stub;
+
+DROP FUNCTION notify_stub_change();
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE FUNCTION notify_stub_change() RETURNS TRIGGER AS $$
+BEGIN
+  PERFORM pg_notify('stub_changes', COALESCE(NEW.id, OLD.id)::text);
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER stub_changed
+  AFTER INSERT OR UPDATE OR DELETE ON stub
+  FOR EACH ROW EXECUTE FUNCTION notify_stub_change();
\ No newline at end of file
@@ -1,10 +1,23 @@
 use crate::api::model::*;
-use crate::api::admin::AdminApiHandler;
-use actix_web::{get, post, web, HttpResponse, Responder};
+use crate::api::admin::{parse_stub_request, AdminApiHandler};
+use crate::api::public::PublicApiHandler;
+use crate::model::HttpMethod;
+use crate::model::persistent;
+use crate::model::persistent::HttpStubResponse;
+use crate::utils::form::urlencoded_to_json;
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, HttpResponseBuilder, Responder};
+use actix_web::http::{Method, StatusCode};
+use actix_web::http::header::{HeaderMap, CONTENT_TYPE};
 use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
 
 pub mod admin;
+pub mod callback;
 pub mod model;
+pub mod persister;
+pub mod proxy;
+pub mod public;
 pub mod resolver;
 
 #[derive(Deserialize)]
@@ -12,14 +25,114 @@ pub struct PathInfo {
     path: String
 }
 
-#[get("/api/rustybird/exec/{path:.*}")]
-pub async fn exec_get(path: web::Path<PathInfo>) -> impl Responder {
-    HttpResponse::Ok().body(format!("{}", path.path))
+pub const EXEC_PATH: &str = "/api/rustybird/exec/{path:.*}";
+
+/// Method-agnostic handler, should be registered on [EXEC_PATH] via `web::route()`
+pub async fn exec(path: web::Path<PathInfo>, req: HttpRequest, body: web::Bytes, handler: web::Data<PublicApiHandler>) -> impl Responder {
+    let method = match to_http_method(req.method()) {
+        Some(m) => m,
+        None => return HttpResponse::MethodNotAllowed().body(format!("Method {} is not supported", req.method()))
+    };
+
+    let exec_request = ExecRequest {
+        method,
+        path: format!("/{}", path.path),
+        headers: headers_to_map(req.headers()),
+        query: query_to_json(req.query_string()),
+        body
+    };
+
+    match handler.exec(exec_request).await {
+        Ok(response) => render_response(response),
+        Err(e) => render_error(e)
+    }
+}
+
+fn to_http_method(method: &Method) -> Option<HttpMethod> {
+    match *method {
+        Method::GET => Some(HttpMethod::Get),
+        Method::POST => Some(HttpMethod::Post),
+        Method::HEAD => Some(HttpMethod::Head),
+        Method::OPTIONS => Some(HttpMethod::Options),
+        Method::PATCH => Some(HttpMethod::Patch),
+        Method::PUT => Some(HttpMethod::Put),
+        Method::DELETE => Some(HttpMethod::Delete),
+        _ => None
+    }
+}
+
+fn to_reqwest_method(method: HttpMethod) -> reqwest::Method {
+    match method {
+        HttpMethod::Get => reqwest::Method::GET,
+        HttpMethod::Post => reqwest::Method::POST,
+        HttpMethod::Head => reqwest::Method::HEAD,
+        HttpMethod::Options => reqwest::Method::OPTIONS,
+        HttpMethod::Patch => reqwest::Method::PATCH,
+        HttpMethod::Put => reqwest::Method::PUT,
+        HttpMethod::Delete => reqwest::Method::DELETE
+    }
+}
+
+/// Collects headers with lowercase names, values of repeated headers are joined with a comma
+fn headers_to_map(headers: &HeaderMap) -> HashMap<String, String> {
+    let mut res: HashMap<String, String> = HashMap::new();
+
+    for (name, value) in headers.iter() {
+        if let Ok(v) = value.to_str() {
+            res.entry(name.as_str().to_string())
+                .and_modify(|existing| {
+                    existing.push_str(", ");
+                    existing.push_str(v);
+                })
+                .or_insert_with(|| v.to_string());
+        }
+    }
+
+    res
+}
+
+/// Converts query string into a JSON object, values of repeated parameters are collected into arrays
+fn query_to_json(query_string: &str) -> Value {
+    urlencoded_to_json(query_string)
+}
+
+fn render_error(error: ExecError) -> HttpResponse {
+    match error {
+        ExecError::NoStub(cause) => HttpResponse::NotFound().body(cause),
+        ExecError::Ambiguous(cause) => HttpResponse::BadRequest().body(cause),
+        ExecError::Upstream(cause) => HttpResponse::BadGateway().body(cause),
+        ExecError::Internal(cause) => HttpResponse::InternalServerError().body(cause)
+    }
+}
+
+fn render_response(response: HttpStubResponse) -> HttpResponse {
+    match response {
+        HttpStubResponse::RawResponse { code, headers, body, .. } => response_builder(code, headers, None).body(body),
+        HttpStubResponse::JsonResponse { code, headers, body, .. } => response_builder(code, headers, None).json(body),
+        HttpStubResponse::XmlResponse { code, headers, body, .. } => response_builder(code, headers, Some("application/xml")).body(body),
+        HttpStubResponse::BinaryResponse { code, headers, body, .. } =>
+            response_builder(code, headers, Some("application/octet-stream")).body(body),
+        // proxy and file responses are replaced by the handler
+        HttpStubResponse::ProxyResponse { .. } | HttpStubResponse::JsonProxyResponse { .. } =>
+            HttpResponse::InternalServerError().body("Proxy response was not forwarded"),
+        HttpStubResponse::FileResponse { .. } =>
+            HttpResponse::InternalServerError().body("File response was not loaded")
+    }
 }
 
-#[post("/api/rustybird/exec/{path:.*}")]
-pub async fn exec_post(path: web::Path<PathInfo>, body: String) -> impl Responder {
-    HttpResponse::Ok().body(format!("{} {}", path.path, body))
+/// Content-Length is always computed from the actual body, so it is not taken from stub headers
+fn response_builder(code: u16, headers: HashMap<String, String>, default_content_type: Option<&str>) -> HttpResponseBuilder {
+    let mut builder = HttpResponse::build(StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR));
+
+    if let Some(content_type) = default_content_type {
+        builder.content_type(content_type);
+    }
+
+    for header in headers.into_iter().filter(|(name, _)| !name.eq_ignore_ascii_case("content-length")) {
+        builder.insert_header(header);
+    }
+
+    builder
 }
 
 // ******************** Admin API ********************
@@ -33,9 +146,211 @@ pub async fn fetch_states(req: web::Json<SearchRequest>, handler: web::Data<Admi
 }
 
 #[post("/api/internal/rustybird/stub")]
-pub async fn create_stub(req: web::Json<CreateStubRequest>, handler: web::Data<AdminApiHandler>) -> impl Responder {
-    match handler.create_stub(req.into_inner()).await {
-        Ok(_) => HttpResponse::Ok().finish(),
+pub async fn create_stub(req: web::Json<Value>, handler: web::Data<AdminApiHandler>) -> impl Responder {
+    let req_stub = match parse_stub_request(req.into_inner()) {
+        Ok(req_stub) => req_stub,
+        Err(e) => return HttpResponse::UnprocessableEntity().json(e)
+    };
+
+    match handler.create_stub(req_stub).await {
+        Ok(stub) => HttpResponse::Ok().json(CreateStubResponse { status: "Stub created".to_string(), id: stub.id, stub }),
+        Err(e) => render_admin_error(e)
+    }
+}
+
+#[get("/api/internal/rustybird/stub/{id}")]
+pub async fn get_stub(id: web::Path<i32>, handler: web::Data<AdminApiHandler>) -> impl Responder {
+    match handler.get_stub(id.into_inner()).await {
+        Ok(Some(stub)) => HttpResponse::Ok().json(stub),
+        Ok(None) => HttpResponse::NotFound().body("Stub not found"),
+        Err(e) => render_admin_error(e)
+    }
+}
+
+#[get("/api/internal/rustybird/stubs")]
+pub async fn list_stubs(req: web::Query<StubListRequest>, handler: web::Data<AdminApiHandler>) -> impl Responder {
+    match handler.list_stubs(req.into_inner()).await {
+        Ok(stubs) => HttpResponse::Ok().json(stubs),
+        Err(e) => render_admin_error(e)
+    }
+}
+
+#[put("/api/internal/rustybird/stub/{id}")]
+pub async fn update_stub(
+    id: web::Path<i32>,
+    req: web::Json<Value>,
+    handler: web::Data<AdminApiHandler>
+) -> impl Responder {
+    let id = id.into_inner();
+
+    let req_stub = match parse_stub_request(req.into_inner()) {
+        Ok(req_stub) => req_stub,
+        Err(e) => return HttpResponse::UnprocessableEntity().json(e)
+    };
+
+    match handler.update_stub(id, req_stub).await {
+        Ok(true) => HttpResponse::Ok().json(OperationResult::new("Stub updated", Some(id))),
+        Ok(false) => HttpResponse::NotFound().body("Stub not found"),
+        Err(e) => render_admin_error(e)
+    }
+}
+
+#[delete("/api/internal/rustybird/stub/{id}")]
+pub async fn delete_stub(id: web::Path<i32>, handler: web::Data<AdminApiHandler>) -> impl Responder {
+    let id = id.into_inner();
+
+    match handler.delete_stub(id).await {
+        Ok(true) => HttpResponse::Ok().json(OperationResult::new("Stub removed", Some(id))),
+        Ok(false) => HttpResponse::NotFound().body("Stub not found"),
+        Err(e) => render_admin_error(e)
+    }
+}
+
+/// Rejected stub definitions are described with [StubError], other failures are server errors
+fn render_admin_error(error: AdminError) -> HttpResponse {
+    match error {
+        AdminError::Invalid(cause) => HttpResponse::UnprocessableEntity().json(StubError::new(&cause, vec![])),
+        AdminError::Internal(cause) => HttpResponse::InternalServerError().body(cause)
+    }
+}
+
+#[post("/api/internal/rustybird/service")]
+pub async fn create_service(req: web::Json<persistent::Service>, handler: web::Data<AdminApiHandler>) -> impl Responder {
+    match handler.create_service(req.into_inner()).await {
+        Ok(service) => HttpResponse::Ok().json(OperationResult::new("Service created", Some(service.suffix))),
         Err(e) => HttpResponse::UnprocessableEntity().body(e.cause)
     }
+}
+
+#[get("/api/internal/rustybird/service")]
+pub async fn list_services(handler: web::Data<AdminApiHandler>) -> impl Responder {
+    match handler.list_services().await {
+        Ok(services) => HttpResponse::Ok().json(services),
+        Err(e) => HttpResponse::BadRequest().body(e.cause)
+    }
+}
+
+/// Blob content is the request body, its content type is taken from the `Content-Type` header
+#[post("/api/internal/rustybird/blob")]
+pub async fn upload_blob(
+    req: HttpRequest,
+    query: web::Query<BlobUploadRequest>,
+    body: web::Bytes,
+    handler: web::Data<AdminApiHandler>
+) -> impl Responder {
+    let content_type = req.headers().get(CONTENT_TYPE)
+        .and_then(|ct| ct.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+
+    match handler.upload_blob(query.into_inner().name, content_type, body.to_vec()).await {
+        Ok(id) => HttpResponse::Ok().json(OperationResult::new("Blob uploaded", Some(id))),
+        Err(e) => HttpResponse::UnprocessableEntity().body(e.cause)
+    }
+}
+
+#[get("/api/internal/rustybird/blob")]
+pub async fn list_blobs(handler: web::Data<AdminApiHandler>) -> impl Responder {
+    match handler.list_blobs().await {
+        Ok(blobs) => HttpResponse::Ok().json(blobs),
+        Err(e) => HttpResponse::BadRequest().body(e.cause)
+    }
+}
+
+#[delete("/api/internal/rustybird/blob/{id}")]
+pub async fn delete_blob(id: web::Path<i32>, handler: web::Data<AdminApiHandler>) -> impl Responder {
+    let id = id.into_inner();
+
+    match handler.delete_blob(id).await {
+        Ok(true) => HttpResponse::Ok().json(OperationResult::new("Blob removed", Some(id))),
+        Ok(false) => HttpResponse::NotFound().body("Blob not found"),
+        Err(e) => HttpResponse::UnprocessableEntity().body(e.cause)
+    }
+}
+
+#[cfg(test)]
+mod api_tests {
+    use crate::api::{headers_to_map, query_to_json, render_admin_error, render_error, render_response};
+    use crate::api::model::{AdminError, ExecError};
+    use crate::model::persistent::HttpStubResponse;
+    use actix_web::body::MessageBody;
+    use actix_web::http::StatusCode;
+    use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
+    use serde_json::json;
+
+    #[test]
+    fn query_should_be_converted_to_json_object() {
+        assert_eq!(query_to_json("a=1&b=some%20text"), json!({"a": "1", "b": "some text"}));
+        assert_eq!(query_to_json(""), json!({}));
+    }
+
+    #[test]
+    fn repeated_query_parameters_should_be_collected_into_array() {
+        assert_eq!(query_to_json("a=1&b=2&a=3&a=4"), json!({"a": ["1", "3", "4"], "b": "2"}));
+    }
+
+    #[test]
+    fn header_names_should_be_lowercase() {
+        let mut headers = HeaderMap::new();
+        headers.insert(HeaderName::from_static("x-request-id"), HeaderValue::from_static("42"));
+        headers.append(HeaderName::from_static("accept"), HeaderValue::from_static("text/plain"));
+        headers.append(HeaderName::from_static("accept"), HeaderValue::from_static("text/html"));
+
+        let res = headers_to_map(&headers);
+
+        assert_eq!(res.get("x-request-id"), Some(&"42".to_string()));
+        assert_eq!(res.get("accept"), Some(&"text/plain, text/html".to_string()));
+    }
+
+    #[test]
+    fn binary_response_should_be_decoded_and_typed() {
+        let response = serde_json::from_value::<HttpStubResponse>(json!({
+            "mode": "binary",
+            "code": 200,
+            "headers": {"Content-Length": "100500"},
+            "body": "AAH/"
+        })).unwrap();
+
+        let res = render_response(response);
+
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/octet-stream");
+        assert!(res.headers().get(CONTENT_LENGTH).is_none());
+        assert_eq!(res.into_body().try_into_bytes().ok().unwrap().as_ref(), [0u8, 1, 255]);
+    }
+
+    #[test]
+    fn stub_headers_should_override_default_content_type() {
+        let response = serde_json::from_value::<HttpStubResponse>(json!({
+            "mode": "binary",
+            "code": 200,
+            "headers": {"Content-Type": "image/png"},
+            "body": ""
+        })).unwrap();
+
+        assert_eq!(render_response(response).headers().get(CONTENT_TYPE).unwrap(), "image/png");
+    }
+
+    #[test]
+    fn incorrect_base64_should_be_rejected() {
+        assert!(serde_json::from_value::<HttpStubResponse>(json!({
+            "mode": "binary",
+            "code": 200,
+            "headers": {},
+            "body": "not base64!"
+        })).is_err());
+    }
+
+    #[test]
+    fn exec_errors_should_be_answered_by_kind() {
+        assert_eq!(render_error(ExecError::NoStub("".to_string())).status(), StatusCode::NOT_FOUND);
+        assert_eq!(render_error(ExecError::Ambiguous("".to_string())).status(), StatusCode::BAD_REQUEST);
+        assert_eq!(render_error(ExecError::Upstream("".to_string())).status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(render_error(ExecError::Internal("".to_string())).status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn only_rejected_stubs_should_be_client_errors() {
+        assert_eq!(render_admin_error(AdminError::Invalid("".to_string())).status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(render_admin_error(AdminError::Internal("".to_string())).status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
 }
\ No newline at end of file
@@ -1,43 +1,527 @@
 use crate::api::model::*;
 use crate::dal::*;
+use crate::dal::cache::StubCache;
 use crate::error::Error;
+use crate::model::Scope;
 use crate::model::persistent;
-use chrono::Utc;
+use crate::model::sql_json::StateSpec;
+use crate::predicate_dsl::json::ConditionProblem;
+use crate::utils::js::optic::JsonOptic;
+use crate::utils::transformations::js::JsonTransformations;
+use crate::utils::xml::canonicalize;
+use chrono::{DateTime, Utc};
 use diesel_json::Json;
+use log::error;
+use regex::Regex;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+const STUBS_PER_PAGE: i64 = 20;
+
+static SUFFIX_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-z0-9_\-]{1,40}$").unwrap());
+
+/// Deserializes stub definition, faulty predicate conditions, state query conditions
+/// and `persist` paths are listed one by one
+pub fn parse_stub_request(spec: Value) -> Result<CreateStubRequest, StubError> {
+    let located = |path: String, problem: ConditionProblem| StubProblem {
+        path: Some(path),
+        field: Some(problem.field),
+        keyword: problem.keyword,
+        value: Some(problem.value),
+        reason: problem.reason
+    };
+
+    let mut problems = spec.get("request").map(persistent::HttpStubRequest::predicate_problems).unwrap_or_default()
+        .into_iter()
+        .map(|(location, problem)| located(format!("request.{}", location), problem))
+        .collect::<Vec<_>>();
+
+    if let Some(state) = spec.get("state").filter(|state| !state.is_null()) {
+        problems.extend(StateSpec::problems(state).into_iter().map(|problem| located("state".to_string(), problem)));
+    }
+
+    if let Some(Value::Object(persist)) = spec.get("persist") {
+        problems.extend(persist.iter()
+            .filter(|(path, _)| !JsonOptic::from_path(path).is_well_formed())
+            .map(|(path, value)| StubProblem {
+                path: Some("persist".to_string()),
+                field: Some(path.clone()),
+                keyword: None,
+                value: Some(value.clone()),
+                reason: "Incorrect path".to_string()
+            }));
+    }
+
+    if !problems.is_empty() {
+        return Err(StubError::new("Stub has faulty predicates", problems));
+    }
+
+    serde_json::from_value::<CreateStubRequest>(spec)
+        .map_err(|e| StubError::new("Stub is incorrect", vec![StubProblem::new(e.to_string())]))
+}
 
 #[derive(Clone)]
 pub struct AdminApiHandler {
     stub_dao: StubDao,
-    state_dao: StateDao
+    stub_cache: StubCache,
+    state_dao: StateDao,
+    service_dao: ServiceDao,
+    blob_dao: BlobDao
 }
 
 impl AdminApiHandler {
-    pub fn new(stub_dao: StubDao, state_dao: StateDao) -> AdminApiHandler {
-        AdminApiHandler { stub_dao, state_dao }
+    pub fn new(stub_dao: StubDao, stub_cache: StubCache, state_dao: StateDao, service_dao: ServiceDao, blob_dao: BlobDao) -> AdminApiHandler {
+        AdminApiHandler { stub_dao, stub_cache, state_dao, service_dao, blob_dao }
+    }
+
+    pub async fn create_stub(&self, req_stub: CreateStubRequest) -> Result<persistent::HttpStub, AdminError> {
+        AdminApiHandler::check_scope(&req_stub)?;
+        AdminApiHandler::check_seed(&req_stub)?;
+        let suffix = self.resolve_service(&req_stub).await?;
+        self.check_blob(&req_stub).await?;
+        self.check_conflicts(&req_stub, None).await?;
+
+        let new_stub = AdminApiHandler::make_stub(req_stub, suffix, Utc::now())?;
+
+        let stub = self.stub_dao.insert_stub(new_stub).await?;
+
+        self.refresh_cache(stub.id).await;
+
+        Ok(stub)
+    }
+
+    pub async fn get_stub(&self, id: i32) -> Result<Option<persistent::HttpStub>, AdminError> {
+        Ok(self.stub_dao.get_stub(id).await?)
+    }
+
+    pub async fn list_stubs(&self, request: StubListRequest) -> Result<Vec<persistent::HttpStub>, AdminError> {
+        let page = request.page.unwrap_or(0).max(0);
+
+        let stubs = self.stub_dao.list_stubs(
+            request.name,
+            request.service_suffix,
+            request.method,
+            request.scope,
+            page * STUBS_PER_PAGE,
+            STUBS_PER_PAGE
+        ).await?;
+
+        Ok(stubs)
+    }
+
+    /// Replaces stub definition, keeping its id and creation time. Returns false if there is no such stub
+    pub async fn update_stub(&self, id: i32, req_stub: CreateStubRequest) -> Result<bool, AdminError> {
+        match self.stub_dao.get_stub(id).await? {
+            Some(existing) => {
+                AdminApiHandler::check_scope(&req_stub)?;
+                AdminApiHandler::check_seed(&req_stub)?;
+                let suffix = self.resolve_service(&req_stub).await?;
+                self.check_blob(&req_stub).await?;
+                self.check_conflicts(&req_stub, Some(id)).await?;
+
+                let upd_stub = AdminApiHandler::make_stub(req_stub, suffix, existing.created)?;
+
+                let updated = self.stub_dao.update_stub(id, upd_stub).await? > 0;
+                self.refresh_cache(id).await;
+
+                Ok(updated)
+            }
+            None => Ok(false)
+        }
+    }
+
+    pub async fn delete_stub(&self, id: i32) -> Result<bool, AdminError> {
+        let deleted = self.stub_dao.delete_stub(id).await? > 0;
+        self.refresh_cache(id).await;
+
+        Ok(deleted)
+    }
+
+    pub async fn create_service(&self, new_service: persistent::Service) -> Result<persistent::Service, Error> {
+        if !SUFFIX_PATTERN.is_match(&new_service.suffix) {
+            return Err(Error::new(format!("Incorrect service suffix '{}'", new_service.suffix)));
+        }
+
+        self.service_dao.insert_service(new_service).await
+    }
+
+    pub async fn list_services(&self) -> Result<Vec<persistent::Service>, Error> {
+        self.service_dao.list_services().await
+    }
+
+    pub async fn upload_blob(&self, name: String, content_type: String, content: Vec<u8>) -> Result<i32, Error> {
+        if name.is_empty() || name.len() > 256 {
+            return Err(Error::new("Blob name should be from 1 to 256 characters long".to_string()));
+        }
+
+        self.blob_dao.insert_blob(persistent::NewBlob { created: Utc::now(), name, content_type, content }).await
+    }
+
+    pub async fn list_blobs(&self) -> Result<Vec<persistent::BlobInfo>, Error> {
+        self.blob_dao.list_blobs().await
+    }
+
+    /// Blobs used by stubs can't be removed. Returns false if there is no such blob
+    pub async fn delete_blob(&self, id: i32) -> Result<bool, Error> {
+        let users = self.stub_dao.find_blob_users(id).await?;
+
+        if !users.is_empty() {
+            let ids = users.iter().map(|stub_id| stub_id.to_string()).collect::<Vec<_>>();
+            return Err(Error::new(format!("Blob {} is used by stubs: {}", id, ids.join(", "))));
+        }
+
+        self.blob_dao.delete_blob(id).await.map(|res| res > 0)
+    }
+
+    pub async fn fetch_states(&self, request: SearchRequest) -> Result<Vec<persistent::State>, Error> {
+        self.state_dao.find_by_spec(request.query).await
+    }
+
+    /// The cache is refreshed right away to serve the changes without waiting for the notification.
+    /// The change is already stored, so a failure is left to the notification instead of failing the request
+    async fn refresh_cache(&self, stub_id: i32) {
+        if let Err(e) = self.stub_cache.refresh(&[stub_id]).await {
+            error!("Failed to refresh stub {} in cache: {}", stub_id, e);
+        }
+    }
+
+    fn check_seed(req_stub: &CreateStubRequest) -> Result<(), AdminError> {
+        match &req_stub.seed {
+            Some(seed) if !seed.is_object() => Err(AdminError::Invalid("'seed' should be a JSON object".to_string())),
+            _ => Ok(())
+        }
+    }
+
+    fn check_scope(req_stub: &CreateStubRequest) -> Result<(), AdminError> {
+        match (req_stub.scope, req_stub.times) {
+            (Scope::Countdown, None | Some(0)) => Err(AdminError::Invalid("Countdown stub should have positive 'times'".to_string())),
+            (Scope::Persistent | Scope::Ephemeral, Some(_)) =>
+                Err(AdminError::Invalid("'times' can only be set for countdown stubs".to_string())),
+            _ => Ok(())
+        }
+    }
+
+    /// Checks that the stub path belongs to an existing service and returns the service suffix
+    async fn resolve_service(&self, req_stub: &CreateStubRequest) -> Result<String, AdminError> {
+        let stub_path = req_stub.path.clone()
+            .or(req_stub.path_pattern.as_ref().map(|rx| rx.to_string()))
+            .ok_or_else(|| AdminError::Invalid("Either 'path' or 'path_pattern' should be set".to_string()))?;
+
+        let path_suffix = service_suffix_of(&stub_path)
+            .ok_or_else(|| AdminError::Invalid(format!("Can't determine service for path '{}'", stub_path)))?;
+
+        let suffix = match &req_stub.service_suffix {
+            Some(sfx) if sfx != path_suffix =>
+                return Err(AdminError::Invalid(format!("Path '{}' does not start with service suffix '/{}'", stub_path, sfx))),
+            Some(sfx) => sfx.clone(),
+            None => path_suffix.to_string()
+        };
+
+        match self.service_dao.get_service(&suffix).await? {
+            Some(_) => Ok(suffix),
+            None => Err(AdminError::Invalid(format!("Service '{}' does not exist", suffix)))
+        }
     }
 
-    pub async fn create_stub(&self, req_stub: CreateStubRequest) -> Result<bool, Error> {
-        let new_stub = persistent::NewHttpStub {
-            created: Utc::now(),
+    async fn check_blob(&self, req_stub: &CreateStubRequest) -> Result<(), AdminError> {
+        match &req_stub.response {
+            persistent::HttpStubResponse::FileResponse { blob_id, .. } if self.blob_dao.get_blob(*blob_id).await?.is_none() =>
+                Err(AdminError::Invalid(format!("Blob {} does not exist", blob_id))),
+            _ => Ok(())
+        }
+    }
+
+    /// Rejects a stub if there is another one with the same scope, which may match the same request. Routes are shared
+    /// by stubs with the same path or path pattern and by an exact path matching a path pattern, while different
+    /// patterns are assumed to be disjoint. Stubs on a shared route are told apart only by contradicting conditions,
+    /// see [stub_conditions]. Other overlaps are detected during resolution, which fails if several stubs match
+    async fn check_conflicts(&self, req_stub: &CreateStubRequest, own_id: Option<i32>) -> Result<(), AdminError> {
+        let path_pattern = req_stub.path_pattern.as_ref().map(|rx| rx.to_string());
+
+        let same_route = self.stub_dao.find_same_route(
+            req_stub.method,
+            req_stub.scope,
+            req_stub.path.as_deref(),
+            path_pattern.as_deref()
+        ).await?;
+
+        let conditions = stub_conditions(
+            &serde_json::to_value(&req_stub.request).map_err(Error::from)?,
+            &serde_json::to_value(&req_stub.state).map_err(Error::from)?
+        );
+
+        let conflicting = same_route.into_iter()
+            .filter(|stub| Some(stub.id) != own_id)
+            .filter(|stub| routes_intersect(req_stub.path.as_deref(), path_pattern.as_deref(), stub))
+            .filter(|stub| {
+                let existing = stub_conditions(
+                    &serde_json::to_value(&stub.request).unwrap_or_default(),
+                    &serde_json::to_value(&stub.state).unwrap_or_default()
+                );
+
+                !contradict(&conditions, &existing)
+            })
+            .map(|stub| stub.id.to_string())
+            .collect::<Vec<_>>();
+
+        if conflicting.is_empty() {
+            Ok(())
+        } else {
+            Err(AdminError::Invalid(format!(
+                "There are stubs with the same route and scope, whose request and state conditions overlap: {}",
+                conflicting.join(", ")
+            )))
+        }
+    }
+
+    /// Builds a stub to be stored, seed generators are evaluated here once
+    fn make_stub(req_stub: CreateStubRequest, suffix: String, created: DateTime<Utc>) -> Result<persistent::NewHttpStub, AdminError> {
+        let mut seed = req_stub.seed;
+
+        if let Some(seed_value) = seed.as_mut() {
+            seed_value.eval_in_place().map_err(|e| AdminError::Invalid(e.cause))?;
+        }
+
+        Ok(persistent::NewHttpStub {
+            created,
             scope: req_stub.scope,
             times: req_stub.times.map(|u| u.into()),
-            service_suffix: "".to_string(),
+            service_suffix: suffix,
             name: req_stub.name,
             method: req_stub.method,
             path: req_stub.path,
             path_pattern: req_stub.path_pattern.map(|rx| rx.to_string()),
-            seed: None,
-            state: req_stub.state.map(|spec| Json::new(spec)),
+            seed,
+            state: req_stub.state.map(Json::new),
             request: Json::new(req_stub.request),
-            persist: None,
+            persist: req_stub.persist.map(Json::new),
             response: Json::new(req_stub.response),
-            callback: req_stub.callback.map(|c| Json::new(c))
+            callback: req_stub.callback.map(Json::new)
+        })
+    }
+}
+
+/// Extracts the first segment of a stub path (or path pattern)
+fn service_suffix_of(stub_path: &str) -> Option<&str> {
+    stub_path.trim_start_matches('^')
+        .strip_prefix('/')
+        .and_then(|p| p.split('/').next())
+        .filter(|sfx| !sfx.is_empty())
+}
+
+/// Checks that some request path matches routes of both stubs, different path patterns are assumed to be disjoint
+fn routes_intersect(path: Option<&str>, path_pattern: Option<&str>, stub: &persistent::HttpStub) -> bool {
+    let matches = |pattern: &str, path: &str| persistent::path_regex(pattern).is_ok_and(|rx| rx.is_match(path));
+
+    match ((path, path_pattern), (stub.path.as_deref(), stub.path_pattern.as_deref())) {
+        ((Some(path), _), (Some(other), _)) => path == other,
+        ((Some(path), _), (None, Some(pattern))) => matches(pattern, path),
+        ((None, Some(pattern)), (Some(path), _)) => matches(pattern, path),
+        ((None, Some(pattern)), (None, Some(other))) => pattern == other,
+        _ => false
+    }
+}
+
+/// Collects conditions of serialized request definition and state spec, which require an exact value: header values,
+/// whole bodies of the same mode and `==` conditions of the predicates. Other conditions may hold together, so they are skipped
+fn stub_conditions(request: &Value, state: &Value) -> HashMap<String, Value> {
+    let mut conditions = HashMap::new();
+    let mode = request.get("mode").and_then(Value::as_str).unwrap_or_default();
+
+    for (key, value) in request.as_object().into_iter().flatten() {
+        match (key.as_str(), value) {
+            ("headers", Value::Object(headers)) =>
+                conditions.extend(headers.iter().map(|(name, v)| (json!(["headers", name.to_lowercase()]).to_string(), v.clone()))),
+            ("query", predicate) => predicate_conditions("query", predicate, &mut conditions),
+            ("body", predicate) if matches!(mode, "jlens" | "xpath" | "form") =>
+                predicate_conditions(&format!("{} body", mode), predicate, &mut conditions),
+            ("body", body) if matches!(mode, "json" | "raw") => {
+                conditions.insert(json!([format!("{} body", mode)]).to_string(), body.clone());
+            }
+            // XML bodies are compared in canonical form, since they may differ in formatting only
+            ("body", Value::String(body)) if mode == "xml" => {
+                if let Ok(node) = canonicalize(body) {
+                    conditions.insert(json!([format!("{} body", mode)]).to_string(), Value::String(format!("{:?}", node)));
+                }
+            }
+            _ => ()
+        }
+    }
+
+    predicate_conditions("state", state, &mut conditions);
+
+    conditions
+}
+
+/// Takes `==` conditions of the fields, groups are skipped
+fn predicate_conditions(location: &str, predicate: &Value, conditions: &mut HashMap<String, Value>) {
+    for (field, conds) in predicate.as_object().into_iter().flatten().filter(|(field, _)| !field.starts_with('$')) {
+        if let Some(arg) = conds.get("==") {
+            conditions.insert(json!([location, field]).to_string(), arg.clone());
+        }
+    }
+}
+
+/// Stubs never match the same request if they require different values of the same condition
+fn contradict(conditions: &HashMap<String, Value>, other: &HashMap<String, Value>) -> bool {
+    conditions.iter().any(|(key, value)| other.get(key).is_some_and(|other_value| other_value != value))
+}
+
+#[cfg(test)]
+mod admin_tests {
+    use crate::api::admin::{contradict, parse_stub_request, routes_intersect, service_suffix_of, stub_conditions};
+    use crate::api::model::StubProblem;
+    use crate::model::HttpMethod;
+    use crate::model::persistent::HttpStub;
+    use serde_json::json;
+
+    #[test]
+    fn service_suffix_should_be_extracted_from_path() {
+        assert_eq!(service_suffix_of("/alpha/handler"), Some("alpha"));
+        assert_eq!(service_suffix_of("/alpha"), Some("alpha"));
+        assert_eq!(service_suffix_of("^/alpha/\\d+"), Some("alpha"));
+    }
+
+    #[test]
+    fn service_suffix_should_not_be_extracted_from_relative_path() {
+        assert_eq!(service_suffix_of("alpha/handler"), None);
+        assert_eq!(service_suffix_of("//handler"), None);
+        assert_eq!(service_suffix_of(""), None);
+    }
+
+    #[test]
+    fn faulty_predicates_should_be_listed() {
+        let spec = json!({
+            "scope": "persistent",
+            "name": "test",
+            "method": "POST",
+            "path": "/alpha/handler",
+            "request": {
+                "mode": "multipart",
+                "headers": {},
+                "query": {"q": {"~=": "(", "==": "1"}},
+                "body": [{"name": "meta", "mode": "jlens", "value": {"id": {">": "1"}}}, {"name": "file", "mode": "any"}]
+            },
+            "response": {"mode": "raw", "code": 200, "headers": {}, "body": ""}
+        });
+
+        let error = parse_stub_request(spec).err().unwrap();
+
+        assert_eq!(error.errors.len(), 2);
+        assert!(error.errors.iter().any(|p| p.path.as_deref() == Some("request.query") && p.field.as_deref() == Some("q")
+            && p.keyword.as_deref() == Some("~=") && p.reason.starts_with("Invalid regex")));
+        assert!(error.errors.contains(&StubProblem {
+            path: Some("request.body[0].value".to_string()),
+            field: Some("id".to_string()),
+            keyword: Some(">".to_string()),
+            value: Some(json!("1")),
+            reason: "Argument should be a number".to_string()
+        }));
+    }
+
+    #[test]
+    fn other_problems_should_be_reported_with_reason() {
+        let error = parse_stub_request(json!({"scope": "persistent", "name": "test"})).err().unwrap();
+
+        assert_eq!(error.errors.len(), 1);
+        assert!(error.errors[0].path.is_none());
+        assert!(error.errors[0].reason.contains("method"));
+    }
+
+    #[test]
+    fn overlapping_conditions_should_be_detected() {
+        let overlap = |a: serde_json::Value, a_state: serde_json::Value, b: serde_json::Value, b_state: serde_json::Value| {
+            !contradict(&stub_conditions(&a, &a_state), &stub_conditions(&b, &b_state))
         };
+        let no_state = || json!(null);
+
+        let bare = json!({"mode": "no_body", "headers": {}, "query": {}});
+        let detailed = json!({
+            "mode": "jlens",
+            "headers": {"X-Token": "1"},
+            "query": {"id": {"==": 1, ">": 0}},
+            "body": {"user.name": {"==": "peka"}}
+        });
+
+        assert!(overlap(bare.clone(), no_state(), detailed.clone(), no_state()));
+        assert!(overlap(detailed.clone(), no_state(), json!({"mode": "jlens", "headers": {}, "query": {"id": {"==": 1}}, "body": {}}), no_state()));
+        assert!(!overlap(detailed.clone(), no_state(), json!({"mode": "jlens", "headers": {}, "query": {"id": {"==": 2}}, "body": {}}), no_state()));
+        assert!(!overlap(detailed.clone(), no_state(), json!({"mode": "no_body", "headers": {"x-token": "2"}}), no_state()));
+        assert!(overlap(detailed, no_state(), json!({"mode": "form", "headers": {}, "query": {}, "body": {"user.name": {"==": "lol"}}}), no_state()));
+
+        // a request with both a=1 and b=2 matches the both stubs
+        assert!(overlap(
+            json!({"mode": "no_body", "headers": {}, "query": {"a": {"==": 1}}}),
+            no_state(),
+            json!({"mode": "no_body", "headers": {}, "query": {"b": {"==": 2}}}),
+            no_state()
+        ));
+
+        assert!(!overlap(
+            json!({"mode": "raw", "headers": {}, "query": {}, "body": "a"}),
+            no_state(),
+            json!({"mode": "raw", "headers": {}, "query": {}, "body": "b"}),
+            no_state()
+        ));
+        assert!(overlap(
+            json!({"mode": "xml", "headers": {}, "body": "<a><b>1</b></a>"}),
+            no_state(),
+            json!({"mode": "xml", "headers": {}, "body": "<a>\n  <b>1</b>\n</a>"}),
+            no_state()
+        ));
+        assert!(!overlap(
+            json!({"mode": "xml", "headers": {}, "body": "<a><b>1</b></a>"}),
+            no_state(),
+            json!({"mode": "xml", "headers": {}, "body": "<a><b>2</b></a>"}),
+            no_state()
+        ));
 
-        self.stub_dao.insert_stub(new_stub).await.map(|res| res > 0)
+        assert!(overlap(bare.clone(), no_state(), bare.clone(), json!({"user.id": {"==": 1}})));
+        assert!(!overlap(bare.clone(), json!({"user.id": {"==": 2}}), bare, json!({"user.id": {"==": 1}})));
     }
 
-    pub async fn fetch_states(&self, request: SearchRequest) -> Result<Vec<persistent::State>, Error> {
-        self.state_dao.find_by_spec(request.query).await
+    #[test]
+    fn exact_paths_should_share_route_with_matching_patterns() {
+        let exact = HttpStub::at_route(1, HttpMethod::Get, Some("/alpha/orders/42"), None);
+        let pattern = HttpStub::at_route(2, HttpMethod::Get, None, Some(r"/alpha/orders/\d+"));
+
+        assert!(routes_intersect(Some("/alpha/orders/42"), None, &exact));
+        assert!(!routes_intersect(Some("/alpha/orders"), None, &exact));
+        assert!(routes_intersect(Some("/alpha/orders/42"), None, &pattern));
+        assert!(!routes_intersect(Some("/alpha/orders/42/items"), None, &pattern));
+        assert!(routes_intersect(None, Some(r"/alpha/orders/\d+"), &exact));
+        assert!(!routes_intersect(None, Some(r"/alpha/users/\d+"), &exact));
+        assert!(routes_intersect(None, Some(r"/alpha/orders/\d+"), &pattern));
+        assert!(!routes_intersect(None, Some(r"/alpha/orders/.+"), &pattern));
+    }
+
+    #[test]
+    fn faulty_state_and_persist_should_be_listed() {
+        let spec = json!({
+            "scope": "persistent",
+            "name": "test",
+            "method": "GET",
+            "path": "/alpha/handler",
+            "state": {"user.id": {"==": 1, "~=": 42}, "$or": [{"user.name": {"like": "a"}}]},
+            "persist": {"user..id": 1, "user.name": "peka"},
+            "request": {"mode": "no_body", "headers": {}},
+            "response": {"mode": "raw", "code": 200, "headers": {}, "body": ""}
+        });
+
+        let error = parse_stub_request(spec).err().unwrap();
+
+        assert_eq!(error.errors.len(), 3);
+        assert!(error.errors.contains(&StubProblem {
+            path: Some("state".to_string()),
+            field: Some("user.id".to_string()),
+            keyword: Some("~=".to_string()),
+            value: Some(json!(42)),
+            reason: "Argument should be a string".to_string()
+        }));
+        assert!(error.errors.iter().any(|p| p.path.as_deref() == Some("state") && p.field.as_deref() == Some("$or[0].user.name")
+            && p.keyword.as_deref() == Some("like") && p.reason == "Unknown keyword"));
+        assert!(error.errors.iter().any(|p| p.path.as_deref() == Some("persist") && p.field.as_deref() == Some("user..id")
+            && p.reason == "Incorrect path"));
     }
 }
\ No newline at end of file
@@ -0,0 +1,273 @@
+use crate::api::persister::StatePersister;
+use crate::api::to_reqwest_method;
+use crate::error::Error;
+use crate::model::HttpMethod;
+use crate::model::persistent::{Callback, CallbackRequest, CallbackResponseMode, State};
+use crate::utils::transformations::js::JsonTransformations;
+use actix_web::rt;
+use log::{error, warn};
+use reqwest::Client;
+use reqwest::header::CONTENT_TYPE;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Failed callback requests are repeated up to `attempts` times in total,
+/// waiting `backoff` before the first retry and doubling it afterwards
+#[derive(Clone)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub backoff: Duration
+}
+
+#[derive(Clone)]
+pub struct CallbackEngine {
+    client: Client,
+    persister: StatePersister,
+    retry_policy: RetryPolicy
+}
+
+struct RenderedRequest {
+    url: String,
+    method: HttpMethod,
+    headers: HashMap<String, String>,
+    body: Option<String>
+}
+
+impl CallbackEngine {
+    pub fn new(persister: StatePersister, retry_policy: RetryPolicy) -> CallbackEngine {
+        CallbackEngine { client: Client::new(), persister, retry_policy }
+    }
+
+    /// Executes callback chain in background, failures are logged
+    pub fn spawn(&self, callback: Callback, context: Value, state: Option<State>) {
+        let engine = self.clone();
+
+        rt::spawn(async move {
+            if let Err(e) = engine.execute(callback, context, state).await {
+                error!("Callback execution failed: {}", e);
+            }
+        });
+    }
+
+    /// Executes callback and its nested callbacks one after another.
+    /// JSON response of a callback is available in templates of subsequent steps as `resp`
+    pub async fn execute(&self, callback: Callback, mut context: Value, mut state: Option<State>) -> Result<(), Error> {
+        let mut next = Some(callback);
+
+        while let Some(Callback::HttpCallback { request, response_mode, persist, callback, delay }) = next {
+            if let Some(delay) = delay {
+                rt::time::sleep(delay.sample()).await;
+            }
+
+            let response_body = self.send(&render_request(request, &context)).await?;
+
+            if let Some(CallbackResponseMode::Json) = response_mode {
+                context["resp"] = serde_json::from_str::<Value>(&response_body)
+                    .map_err(|e| Error::new(format!("Callback response is not a valid JSON: {}", e)))?;
+            }
+
+            if let Some(persist) = persist {
+                let updated = self.persister.persist(&persist, state, context.clone()).await?;
+                context["state"] = updated.data.clone();
+                state = Some(updated);
+            }
+
+            next = callback.map(|nested| *nested);
+        }
+
+        Ok(())
+    }
+
+    async fn send(&self, request: &RenderedRequest) -> Result<String, Error> {
+        let mut backoff = self.retry_policy.backoff;
+        let mut attempt = 1;
+
+        loop {
+            match self.send_once(request).await {
+                Ok(body) => return Ok(body),
+                Err(e) if attempt < self.retry_policy.attempts => {
+                    warn!("Callback to {} failed (attempt {}): {}", request.url, attempt, e);
+                    rt::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e)
+            }
+        }
+    }
+
+    async fn send_once(&self, request: &RenderedRequest) -> Result<String, Error> {
+        let mut builder = self.client.request(to_reqwest_method(request.method), &request.url);
+
+        for (name, value) in request.headers.iter() {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = &request.body {
+            builder = builder.body(body.clone());
+        }
+
+        let response = builder.send().await.map_err(Error::from)?;
+        let status = response.status();
+        let body = response.text().await.map_err(Error::from)?;
+
+        if status.is_success() {
+            Ok(body)
+        } else {
+            Err(Error::new(format!("Callback to {} responded with {}: {}", request.url, status, body)))
+        }
+    }
+}
+
+/// Substitutes context values into url, headers and body of a callback request
+fn render_request(request: CallbackRequest, context: &Value) -> RenderedRequest {
+    let (url, method, headers, body, is_json) = match request {
+        CallbackRequest::CallbackRequestWithoutBody { url, method, headers } => (url, method, headers, Value::Null, false),
+        CallbackRequest::RawCallbackRequest { url, method, headers, body } => (url, method, headers, Value::String(body), false),
+        CallbackRequest::JsonCallbackRequest { url, method, headers, body } => (url, method, headers, body, true)
+    };
+
+    let mut parts = json!({"url": url, "headers": headers, "body": body});
+    parts.substitute_in_place(context.clone());
+
+    let mut headers = parts["headers"].as_object()
+        .map(|hs| hs.iter().map(|(k, v)| (k.clone(), render_string(v))).collect::<HashMap<_, _>>())
+        .unwrap_or_default();
+
+    let body = match (&parts["body"], is_json) {
+        (Value::Null, false) => None,
+        (json_body, true) => {
+            if !headers.keys().any(|name| name.eq_ignore_ascii_case(CONTENT_TYPE.as_str())) {
+                headers.insert(CONTENT_TYPE.to_string(), "application/json".to_string());
+            }
+            Some(json_body.to_string())
+        }
+        (raw_body, false) => Some(render_string(raw_body))
+    };
+
+    RenderedRequest { url: render_string(&parts["url"]), method, headers, body }
+}
+
+fn render_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string()
+    }
+}
+
+#[cfg(test)]
+mod callback_tests {
+    use crate::api::callback::{CallbackEngine, RetryPolicy};
+    use crate::api::persister::StatePersister;
+    use crate::dal::StateDao;
+    use crate::model::persistent::Callback;
+    use actix_web::{App, HttpRequest, HttpResponse, HttpServer, rt, web};
+    use actix_web::dev::ServerHandle;
+    use diesel::PgConnection;
+    use diesel::r2d2::{ConnectionManager, Pool};
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    type Received = Arc<Mutex<Vec<(String, String)>>>;
+
+    /// Starts a server which records requests and fails the first `failures` of them
+    fn start_server(failures: usize) -> (String, Received, ServerHandle) {
+        let received: Received = Arc::new(Mutex::new(vec![]));
+        let recorder = received.clone();
+
+        let server = HttpServer::new(move || {
+            let recorder = recorder.clone();
+            App::new().default_service(web::to(move |req: HttpRequest, body: String| {
+                let recorder = recorder.clone();
+                async move {
+                    let mut calls = recorder.lock().unwrap();
+                    calls.push((req.path().to_string(), body));
+                    if calls.len() <= failures {
+                        HttpResponse::InternalServerError().finish()
+                    } else {
+                        HttpResponse::Ok().json(json!({"ticket": format!("t{}", calls.len())}))
+                    }
+                }
+            }))
+        })
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+
+        let address = format!("http://{}", server.addrs()[0]);
+        let running = server.run();
+        let handle = running.handle();
+        rt::spawn(running);
+
+        (address, received, handle)
+    }
+
+    fn engine(attempts: u32) -> CallbackEngine {
+        let pool = Pool::builder().build_unchecked(ConnectionManager::<PgConnection>::new("postgres://localhost/unused"));
+
+        CallbackEngine::new(
+            StatePersister::new(StateDao::new(pool)),
+            RetryPolicy { attempts, backoff: Duration::from_millis(10) }
+        )
+    }
+
+    #[actix_web::test]
+    async fn callbacks_should_be_templated_and_chained() {
+        let (address, received, handle) = start_server(0);
+
+        let callback = serde_json::from_value::<Callback>(json!({"HttpCallback": {
+            "request": {
+                "mode": "json",
+                "url": format!("{}/first/${{req.id}}", address),
+                "method": "POST",
+                "headers": {},
+                "body": {"id": "${req.id}", "token": "${seed.token}"}
+            },
+            "response_mode": "Json",
+            "callback": {"HttpCallback": {
+                "request": {
+                    "mode": "raw",
+                    "url": format!("{}/second", address),
+                    "method": "PUT",
+                    "headers": {},
+                    "body": "ticket=${resp.ticket}"
+                },
+                "delay": "10 millis"
+            }}
+        }})).unwrap();
+
+        let res = engine(1).execute(callback, json!({"req": {"id": "42"}, "seed": {"token": "abc"}}), None).await;
+        handle.stop(true).await;
+
+        assert!(res.is_ok());
+
+        let calls = received.lock().unwrap().clone();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].0, "/first/42");
+        assert_eq!(serde_json::from_str::<Value>(&calls[0].1).unwrap(), json!({"id": "42", "token": "abc"}));
+        assert_eq!(calls[1], ("/second".to_string(), "ticket=t1".to_string()));
+    }
+
+    #[actix_web::test]
+    async fn failed_callbacks_should_be_retried() {
+        let (address, received, handle) = start_server(2);
+
+        let callback = serde_json::from_value::<Callback>(json!({"HttpCallback": {
+            "request": {"mode": "no_body", "url": format!("{}/notify", address), "method": "GET", "headers": {}}
+        }})).unwrap();
+
+        let exhausted = engine(2).execute(callback, json!({}), None).await;
+        assert!(exhausted.is_err());
+
+        let callback = serde_json::from_value::<Callback>(json!({"HttpCallback": {
+            "request": {"mode": "no_body", "url": format!("{}/notify", address), "method": "GET", "headers": {}}
+        }})).unwrap();
+
+        let succeeded = engine(2).execute(callback, json!({}), None).await;
+        handle.stop(true).await;
+
+        assert!(succeeded.is_ok());
+        assert_eq!(received.lock().unwrap().len(), 3);
+    }
+}
\ No newline at end of file
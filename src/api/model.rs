@@ -1,9 +1,11 @@
+use crate::error::Error;
 use crate::model::*;
-use crate::model::sql_json::Keyword as JsonKeyword;
+use crate::model::sql_json::StateSpec;
 use crate::utils::js::optic::JsonOptic;
+use actix_web::web::Bytes;
 use regex::Regex;
-use serde::{Deserialize};
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::collections::HashMap;
 
 #[derive(Deserialize)]
@@ -12,6 +14,9 @@ pub struct CreateStubRequest {
     #[serde(default)]
     pub times: Option<u32>,
     pub name: String,
+    /// Derived from the first segment of `path` or `path_pattern` when omitted
+    #[serde(default)]
+    pub service_suffix: Option<String>,
     pub method: HttpMethod,
     #[serde(default)]
     pub path: Option<String>,
@@ -19,7 +24,9 @@ pub struct CreateStubRequest {
     #[serde(default)]
     pub path_pattern: Option<Regex>,
     #[serde(default)]
-    pub state: Option<HashMap<JsonOptic, HashMap<JsonKeyword, Value>>>,
+    pub seed: Option<Value>,
+    #[serde(default)]
+    pub state: Option<StateSpec>,
     pub request: persistent::HttpStubRequest,
     #[serde(default)]
     pub persist: Option<HashMap<JsonOptic, Value>>,
@@ -27,7 +34,136 @@ pub struct CreateStubRequest {
     pub callback: Option<persistent::Callback>
 }
 
+#[derive(Deserialize)]
+pub struct StubListRequest {
+    #[serde(default)]
+    pub page: Option<i64>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, alias = "service")]
+    pub service_suffix: Option<String>,
+    #[serde(default)]
+    pub method: Option<HttpMethod>,
+    #[serde(default)]
+    pub scope: Option<Scope>
+}
+
+#[derive(Serialize)]
+pub struct OperationResult<T: Serialize> {
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<T>
+}
+
+impl<T: Serialize> OperationResult<T> {
+    pub fn new(status: &str, id: Option<T>) -> OperationResult<T> {
+        OperationResult { status: status.to_string(), id }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BlobUploadRequest {
+    pub name: String
+}
+
+#[derive(Serialize)]
+pub struct CreateStubResponse {
+    pub status: String,
+    pub id: i32,
+    pub stub: persistent::HttpStub
+}
+
+/// Reasons of a stub rejection
+#[derive(Serialize)]
+pub struct StubError {
+    pub status: String,
+    pub errors: Vec<StubProblem>
+}
+
+/// Faulty predicate conditions are described by `path` to the predicate in the stub, `field`, `keyword`
+/// and `value` of the condition, other problems have a `reason` only
+#[derive(Debug, PartialEq, Serialize)]
+pub struct StubProblem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    pub reason: String
+}
+
+impl StubError {
+    pub fn new(status: &str, errors: Vec<StubProblem>) -> StubError {
+        StubError { status: status.to_string(), errors }
+    }
+}
+
+impl StubProblem {
+    pub fn new(reason: String) -> StubProblem {
+        StubProblem { path: None, field: None, keyword: None, value: None, reason }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct SearchRequest {
-    pub query: HashMap<JsonOptic, HashMap<JsonKeyword, Value>>
+    pub query: StateSpec
+}
+
+/// Incoming request to be served with a stub
+pub struct ExecRequest {
+    pub method: HttpMethod,
+    pub path: String,
+    /// Header names are lowercase
+    pub headers: HashMap<String, String>,
+    pub query: Value,
+    pub body: Bytes
+}
+
+impl ExecRequest {
+    /// Builds an object with request data, available for substitution in stub templates
+    pub fn template_context(&self, path_parts: Value, seed: Option<&Value>, state: Option<&Value>) -> Value {
+        json!({
+            "req": serde_json::from_slice::<Value>(&self.body).unwrap_or(Value::Null),
+            "query": self.query,
+            "headers": self.headers,
+            "pathParts": path_parts,
+            "seed": seed,
+            "state": state
+        })
+    }
+}
+
+/// Reasons of a failed request execution, each one is answered with its own status
+#[derive(Debug)]
+pub enum ExecError {
+    /// No stub matches the request
+    NoStub(String),
+    /// Several stubs or states match the request
+    Ambiguous(String),
+    /// Proxied request has failed or returned unusable response
+    Upstream(String),
+    Internal(String)
+}
+
+impl From<Error> for ExecError {
+    fn from(value: Error) -> Self {
+        ExecError::Internal(value.cause)
+    }
+}
+
+/// Reasons of a failed stub operation
+#[derive(Debug)]
+pub enum AdminError {
+    /// Stub definition is not acceptable
+    Invalid(String),
+    Internal(String)
+}
+
+impl From<Error> for AdminError {
+    fn from(value: Error) -> Self {
+        AdminError::Internal(value.cause)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,40 @@
+use crate::dal::StateDao;
+use crate::error::Error;
+use crate::model::persistent::State;
+use crate::utils::js::optic::{JsonOptic, ValueExt};
+use crate::utils::transformations::js::JsonTransformations;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+#[derive(Clone)]
+pub struct StatePersister {
+    state_dao: StateDao
+}
+
+impl StatePersister {
+    pub fn new(state_dao: StateDao) -> StatePersister {
+        StatePersister { state_dao }
+    }
+
+    /// Applies `persist` spec onto the state, creating a new state if there is none.
+    /// Current state data is available in templates as `state`
+    pub async fn persist(&self, persist: &HashMap<JsonOptic, Value>, state: Option<State>, mut context: Value) -> Result<State, Error> {
+        let mut data = state.as_ref().map(|st| st.data.clone()).unwrap_or(json!({}));
+
+        context["state"] = data.clone();
+
+        let mut patch = serde_json::to_value(persist).map_err(Error::from)?;
+        patch.substitute_in_place(context);
+
+        if let Value::Object(fields) = patch {
+            for (path, value) in fields {
+                data.set(&JsonOptic::from_path(&path), &value);
+            }
+        }
+
+        match state {
+            Some(st) => self.state_dao.update_state(st.id, data).await,
+            None => self.state_dao.create_state(data).await
+        }
+    }
+}
\ No newline at end of file
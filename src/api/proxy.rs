@@ -0,0 +1,229 @@
+use crate::api::model::ExecRequest;
+use crate::api::to_reqwest_method;
+use crate::error::Error;
+use crate::model::persistent::HttpStubResponse;
+use crate::utils::transformations::js::JsonTransformations;
+use reqwest::Client;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Headers which describe a single connection or encoding and are not passed through the proxy
+const SKIPPED_HEADERS: [&str; 6] = ["host", "connection", "content-length", "transfer-encoding", "accept-encoding", "keep-alive"];
+
+#[derive(Clone, Default)]
+pub struct Proxy {
+    client: Client
+}
+
+struct ProxiedResponse {
+    code: u16,
+    headers: HashMap<String, String>,
+    body: Vec<u8>
+}
+
+impl Proxy {
+    pub fn new() -> Proxy {
+        Proxy { client: Client::new() }
+    }
+
+    /// Replaces proxy responses with upstream ones, other responses are returned unchanged.
+    /// Upstream JSON body is available in the patch templates as `resp`
+    pub async fn resolve(&self, response: HttpStubResponse, request: &ExecRequest, context: &Value) -> Result<HttpStubResponse, Error> {
+        match response {
+            HttpStubResponse::ProxyResponse { uri, delay } => {
+                let proxied = self.forward(&render_uri(uri, context), request).await?;
+
+                Ok(HttpStubResponse::BinaryResponse { code: proxied.code, headers: proxied.headers, body: proxied.body, delay })
+            }
+            HttpStubResponse::JsonProxyResponse { uri, patch, delay } => {
+                let proxied = self.forward(&render_uri(uri, context), request).await?;
+
+                let mut body = serde_json::from_slice::<Value>(&proxied.body)
+                    .map_err(|e| Error::new(format!("Upstream response is not a valid JSON: {}", e)))?;
+
+                let mut values = context.clone();
+                values["resp"] = body.clone();
+                body.patch_in_place(values, patch);
+
+                Ok(HttpStubResponse::JsonResponse { code: proxied.code, headers: proxied.headers, body, delay, is_template: false })
+            }
+            other => Ok(other)
+        }
+    }
+
+    /// Sends the request to `uri` keeping its method, headers, query and body
+    async fn forward(&self, uri: &str, request: &ExecRequest) -> Result<ProxiedResponse, Error> {
+        let mut builder = self.client
+            .request(to_reqwest_method(request.method), uri)
+            .query(&query_pairs(&request.query))
+            .body(request.body.clone());
+
+        for (name, value) in request.headers.iter().filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str())) {
+            builder = builder.header(name, value);
+        }
+
+        let response = builder.send().await.map_err(Error::from)?;
+
+        let code = response.status().as_u16();
+        let headers = response.headers().iter()
+            .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| value.to_str().ok().map(|v| (name.to_string(), v.to_string())))
+            .collect::<HashMap<_, _>>();
+        let body = response.bytes().await.map_err(Error::from)?.to_vec();
+
+        Ok(ProxiedResponse { code, headers, body })
+    }
+}
+
+fn render_uri(uri: String, context: &Value) -> String {
+    let mut uri = Value::String(uri);
+    uri.substitute_in_place(context.clone());
+
+    match uri {
+        Value::String(s) => s,
+        other => other.to_string()
+    }
+}
+
+/// Converts query object back into parameters, arrays become repeated parameters
+fn query_pairs(query: &Value) -> Vec<(String, String)> {
+    let plain = |v: &Value| match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string()
+    };
+
+    query.as_object()
+        .map(|params| params.iter()
+            .flat_map(|(name, value)| match value {
+                Value::Array(values) => values.iter().map(|v| (name.clone(), plain(v))).collect::<Vec<_>>(),
+                single => vec![(name.clone(), plain(single))]
+            })
+            .collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod proxy_tests {
+    use crate::api::model::ExecRequest;
+    use crate::api::proxy::Proxy;
+    use crate::model::HttpMethod;
+    use crate::model::persistent::HttpStubResponse;
+    use actix_web::{App, HttpRequest, HttpResponse, HttpServer, rt, web};
+    use actix_web::dev::ServerHandle;
+    use actix_web::web::Bytes;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    /// Starts an upstream which describes the received request in its JSON response
+    fn start_upstream() -> (String, ServerHandle) {
+        let server = HttpServer::new(|| {
+            App::new().default_service(web::to(|req: HttpRequest, body: String| async move {
+                if req.path() == "/image" {
+                    return HttpResponse::Ok().content_type("image/png").body(vec![0u8, 159, 146, 150, 255]);
+                }
+
+                HttpResponse::Created()
+                    .insert_header(("x-upstream", "yes"))
+                    .json(json!({
+                        "method": req.method().as_str(),
+                        "path": req.path(),
+                        "query": req.query_string(),
+                        "token": req.headers().get("x-token").and_then(|v| v.to_str().ok()),
+                        "body": body,
+                        "status": "real"
+                    }))
+            }))
+        })
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+
+        let address = format!("http://{}", server.addrs()[0]);
+        let running = server.run();
+        let handle = running.handle();
+        rt::spawn(running);
+
+        (address, handle)
+    }
+
+    fn request() -> ExecRequest {
+        ExecRequest {
+            method: HttpMethod::Post,
+            path: "/alpha/orders/42".to_string(),
+            headers: HashMap::from([("x-token".to_string(), "secret".to_string()), ("host".to_string(), "rustybird".to_string())]),
+            query: json!({"tag": ["a", "b"]}),
+            body: Bytes::from_static(b"{\"amount\": 10}")
+        }
+    }
+
+    #[actix_web::test]
+    async fn proxy_should_return_upstream_response() {
+        let (address, handle) = start_upstream();
+
+        let response = serde_json::from_value::<HttpStubResponse>(json!({
+            "mode": "proxy",
+            "uri": format!("{}/orders/${{pathParts.id}}", address)
+        })).unwrap();
+
+        let res = Proxy::new().resolve(response, &request(), &json!({"pathParts": {"id": "42"}})).await;
+        handle.stop(true).await;
+
+        match res.unwrap() {
+            HttpStubResponse::BinaryResponse { code, headers, body, .. } => {
+                assert_eq!(code, 201);
+                assert_eq!(headers.get("x-upstream").map(String::as_str), Some("yes"));
+                assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), json!({
+                    "method": "POST",
+                    "path": "/orders/42",
+                    "query": "tag=a&tag=b",
+                    "token": "secret",
+                    "body": "{\"amount\": 10}",
+                    "status": "real"
+                }));
+            }
+            other => panic!("Unexpected response {:?}", other)
+        }
+    }
+
+    #[actix_web::test]
+    async fn proxy_should_keep_binary_upstream_body() {
+        let (address, handle) = start_upstream();
+
+        let response = serde_json::from_value::<HttpStubResponse>(json!({"mode": "proxy", "uri": format!("{}/image", address)})).unwrap();
+
+        let res = Proxy::new().resolve(response, &request(), &json!({})).await;
+        handle.stop(true).await;
+
+        match res.unwrap() {
+            HttpStubResponse::BinaryResponse { headers, body, .. } => {
+                assert_eq!(headers.get("content-type").map(String::as_str), Some("image/png"));
+                assert_eq!(body, vec![0u8, 159, 146, 150, 255]);
+            }
+            other => panic!("Unexpected response {:?}", other)
+        }
+    }
+
+    #[actix_web::test]
+    async fn json_proxy_should_patch_upstream_response() {
+        let (address, handle) = start_upstream();
+
+        let response = serde_json::from_value::<HttpStubResponse>(json!({
+            "mode": "json-proxy",
+            "uri": format!("{}/orders", address),
+            "patch": {"status": "mocked for ${req.amount}", "echo": "${resp.method}"}
+        })).unwrap();
+
+        let res = Proxy::new().resolve(response, &request(), &json!({"req": {"amount": 10}})).await;
+        handle.stop(true).await;
+
+        match res.unwrap() {
+            HttpStubResponse::JsonResponse { code, body, .. } => {
+                assert_eq!(code, 201);
+                assert_eq!(body["status"], json!("mocked for 10"));
+                assert_eq!(body["echo"], json!("POST"));
+                assert_eq!(body["path"], json!("/orders"));
+            }
+            other => panic!("Unexpected response {:?}", other)
+        }
+    }
+}
\ No newline at end of file
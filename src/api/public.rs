@@ -0,0 +1,79 @@
+use crate::api::callback::CallbackEngine;
+use crate::api::model::{ExecError, ExecRequest};
+use crate::api::persister::StatePersister;
+use crate::api::proxy::Proxy;
+use crate::api::resolver::StubResolver;
+use crate::dal::BlobDao;
+use crate::error::Error;
+use crate::model::persistent::HttpStubResponse;
+use actix_web::rt;
+
+#[derive(Clone)]
+pub struct PublicApiHandler {
+    resolver: StubResolver,
+    persister: StatePersister,
+    proxy: Proxy,
+    callback_engine: CallbackEngine,
+    blob_dao: BlobDao
+}
+
+impl PublicApiHandler {
+    pub fn new(
+        resolver: StubResolver,
+        persister: StatePersister,
+        proxy: Proxy,
+        callback_engine: CallbackEngine,
+        blob_dao: BlobDao
+    ) -> PublicApiHandler {
+        PublicApiHandler { resolver, persister, proxy, callback_engine, blob_dao }
+    }
+
+    pub async fn exec(&self, request: ExecRequest) -> Result<HttpStubResponse, ExecError> {
+        let (stub, path_parts, state) = self.resolver.find_stub_and_state(&request).await?
+            .ok_or_else(|| ExecError::NoStub(format!("Can't find any stub for {:?} {}", request.method, request.path)))?;
+
+        let mut context = request.template_context(path_parts, stub.seed.as_ref(), state.as_ref().map(|st| &st.data));
+
+        let response = self.proxy.resolve(stub.response.0.clone(), &request, &context).await
+            .map_err(|e| ExecError::Upstream(e.cause))?;
+        let mut response = self.load_file(response).await?;
+
+        response.render_template(context.clone(), &String::from_utf8_lossy(&request.body));
+
+        let state = match &stub.persist {
+            Some(persist) => {
+                let updated = self.persister.persist(persist, state, context.clone()).await?;
+                context["state"] = updated.data.clone();
+                Some(updated)
+            }
+            None => state
+        };
+
+        if let Some(callback) = &stub.callback {
+            self.callback_engine.spawn(callback.0.clone(), context, state);
+        }
+
+        if let Some(delay) = response.delay() {
+            rt::time::sleep(delay.sample()).await;
+        }
+
+        Ok(response)
+    }
+
+    /// Replaces file response with a binary one holding the blob content
+    async fn load_file(&self, response: HttpStubResponse) -> Result<HttpStubResponse, Error> {
+        match response {
+            HttpStubResponse::FileResponse { code, mut headers, blob_id, delay } => {
+                let blob = self.blob_dao.get_blob(blob_id).await?
+                    .ok_or_else(|| Error::new(format!("Blob {} not found", blob_id)))?;
+
+                if !headers.keys().any(|name| name.eq_ignore_ascii_case("content-type")) {
+                    headers.insert("Content-Type".to_string(), blob.content_type);
+                }
+
+                Ok(HttpStubResponse::BinaryResponse { code, headers, body: blob.content, delay })
+            }
+            other => Ok(other)
+        }
+    }
+}
\ No newline at end of file
@@ -1,10 +1,123 @@
-use crate::model::*;
-use crate::model::persistent::HttpStub;
+use crate::api::model::{ExecError, ExecRequest};
+use crate::dal::{StateDao, StubDao};
+use crate::dal::cache::StubCache;
+use crate::error::Error;
+use crate::model::Scope;
+use crate::model::persistent::{HttpStub, State};
+use crate::model::sql_json::StateSpec;
+use crate::utils::transformations::js::JsonTransformations;
+use chrono::{Duration, Utc};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::sync::Arc;
 
-pub struct StubResolver {}
+/// Stub matching the request along with its `pathParts` and state
+type Resolved = (Arc<HttpStub>, Value, Option<State>);
+
+/// Matching stub with its state lookup result, which is reported only if the stub is selected
+type Candidate = (Arc<HttpStub>, Value, Result<Option<State>, ExecError>);
+
+#[derive(Clone)]
+pub struct StubResolver {
+    stub_cache: StubCache,
+    stub_dao: StubDao,
+    state_dao: StateDao,
+    ephemeral_ttl: Duration
+}
 
 impl StubResolver {
-    fn find_stub_and_state(method: HttpMethod) -> Option<HttpStub> {
-        None
+    pub fn new(stub_cache: StubCache, stub_dao: StubDao, state_dao: StateDao, ephemeral_ttl: Duration) -> StubResolver {
+        StubResolver { stub_cache, stub_dao, state_dao, ephemeral_ttl }
+    }
+
+    /// Finds the single matching stub along with its state. Stubs having a state spec match only if some state
+    /// satisfies the spec, several suitable states fail the request only if their stub is selected
+    pub async fn find_stub_and_state(&self, request: &ExecRequest) -> Result<Option<Resolved>, ExecError> {
+        let candidates = self.stub_cache.find_candidates(request.method, &request.path, Utc::now() - self.ephemeral_ttl).await?;
+
+        let mut by_priority: BTreeMap<Reverse<u8>, Vec<Candidate>> = BTreeMap::new();
+
+        for (stub, path_parts) in candidates {
+            if !(stub.request.check_headers(&request.headers)
+                && stub.request.check_query(&request.query)
+                && stub.request.check_body(&request.body, request.headers.get("content-type").map(String::as_str))) {
+                continue;
+            }
+
+            let state = match &stub.state {
+                Some(spec) => {
+                    let context = request.template_context(path_parts.clone(), stub.seed.as_ref(), None);
+
+                    let mut states = self.state_dao.find_by_spec(substitute_spec(spec, &context)?).await?;
+
+                    match states.len() {
+                        0 => continue,
+                        1 => Ok(Some(states.remove(0))),
+                        n => Err(ExecError::Ambiguous(format!("Found {} states suitable for stub {}", n, stub.id)))
+                    }
+                }
+                None => Ok(None)
+            };
+
+            by_priority.entry(Reverse(stub.scope.priority())).or_default().push((stub, path_parts, state));
+        }
+
+        for (_, mut matched) in by_priority {
+            if matched.len() > 1 {
+                let ids = matched.iter().map(|(stub, _, _)| stub.id.to_string()).collect::<Vec<_>>();
+                return Err(ExecError::Ambiguous(format!(
+                    "Several stubs match {:?} {}: {}",
+                    request.method,
+                    request.path,
+                    ids.join(", ")
+                )));
+            }
+
+            let (stub, path_parts, state) = matched.remove(0);
+            let state = state?;
+
+            // countdown stub may be exhausted by a concurrent request
+            if stub.scope == Scope::Countdown && !self.stub_dao.decrement_times(stub.id).await? {
+                continue;
+            }
+
+            return Ok(Some((stub, path_parts, state)));
+        }
+
+        Ok(None)
+    }
+}
+
+/// Substitutes request data into the values of state spec
+fn substitute_spec(spec: &StateSpec, context: &Value) -> Result<StateSpec, Error> {
+    let mut spec_json = serde_json::to_value(spec).map_err(Error::from)?;
+
+    spec_json.substitute_in_place(context.clone());
+
+    serde_json::from_value(spec_json).map_err(Error::from)
+}
+
+#[cfg(test)]
+mod resolver_tests {
+    use crate::api::resolver::substitute_spec;
+    use crate::model::sql_json::{Keyword, StateSpec};
+    use crate::utils::js::optic::JsonOptic;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn request_data_should_be_substituted_into_state_spec() {
+        let spec = serde_json::from_value::<StateSpec>(json!({
+            "user.id": {"==": "${req.id}"},
+            "user.age": {">": "$~{query.age}"}
+        })).unwrap();
+
+        let res = substitute_spec(&spec, &json!({"req": {"id": "u1"}, "query": {"age": "18"}})).unwrap();
+
+        assert_eq!(res.conditions, HashMap::from([
+            (JsonOptic::from_path("user.id"), HashMap::from([(Keyword::Eq, json!("u1"))])),
+            (JsonOptic::from_path("user.age"), HashMap::from([(Keyword::Greater, json!(18))]))
+        ]));
     }
 }
\ No newline at end of file
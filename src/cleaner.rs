@@ -0,0 +1,33 @@
+use crate::dal::StubDao;
+use actix_web::rt;
+use chrono::{Duration, Utc};
+use log::{error, info};
+
+/// Periodically removes expired ephemeral stubs and exhausted countdown stubs
+pub struct EphemeralCleaner {
+    stub_dao: StubDao,
+    ephemeral_ttl: Duration,
+    interval: std::time::Duration
+}
+
+impl EphemeralCleaner {
+    pub fn new(stub_dao: StubDao, ephemeral_ttl: Duration, interval: std::time::Duration) -> EphemeralCleaner {
+        EphemeralCleaner { stub_dao, ephemeral_ttl, interval }
+    }
+
+    pub fn spawn(self) {
+        rt::spawn(async move {
+            let mut ticker = rt::time::interval(self.interval);
+
+            loop {
+                ticker.tick().await;
+
+                match self.stub_dao.delete_expired(Utc::now() - self.ephemeral_ttl).await {
+                    Ok(0) => (),
+                    Ok(removed) => info!("Removed {} expired stubs", removed),
+                    Err(e) => error!("Failed to remove expired stubs: {}", e)
+                }
+            }
+        });
+    }
+}
\ No newline at end of file
@@ -1,15 +1,17 @@
 use crate::dal::jsonb::{JsonPath, JsonbQueryMethods, Predicate};
 use crate::error::Error;
+use crate::model::{HttpMethod, Scope};
 use crate::model::persistent::*;
-use crate::model::sql_json::{Keyword as SqlKeyword};
-use crate::utils::js::optic::JsonOptic;
-use chrono::Utc;
+use crate::model::sql_json::StateSpec;
+use chrono::{DateTime, Utc};
+use diesel::dsl::sql;
 use diesel::prelude::*;
+use diesel::sql_types::Int4;
 use diesel::PgConnection;
 use diesel::r2d2::{ConnectionManager, Pool};
 use serde_json::Value;
-use std::collections::HashMap;
 
+pub mod cache;
 pub mod error;
 pub mod jsonb;
 
@@ -23,15 +25,213 @@ impl StubDao {
         StubDao { pool }
     }
 
-    pub async fn insert_stub(&self, new_stub: NewHttpStub) -> Result<usize, Error> {
+    pub async fn insert_stub(&self, new_stub: NewHttpStub) -> Result<HttpStub, Error> {
         use crate::schema::stub::dsl::*;
 
         let mut conn = self.pool.get()?;
 
         let res = diesel::insert_into(stub)
             .values(&new_stub)
+            .returning(HttpStub::as_returning())
+            .get_result(&mut conn)?;
+
+        Ok(res)
+    }
+
+    pub async fn get_stub(&self, stub_id: i32) -> Result<Option<HttpStub>, Error> {
+        use crate::schema::stub::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = stub
+            .find(stub_id)
+            .select(HttpStub::as_select())
+            .first(&mut conn)
+            .optional()?;
+
+        Ok(res)
+    }
+
+    /// Lists stubs, newest first. `name_part` is matched case-insensitively as a substring
+    pub async fn list_stubs(
+        &self,
+        name_part: Option<String>,
+        suffix: Option<String>,
+        stub_method: Option<HttpMethod>,
+        stub_scope: Option<Scope>,
+        offset: i64,
+        limit: i64
+    ) -> Result<Vec<HttpStub>, Error> {
+        use crate::schema::stub::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let mut query = stub.into_boxed();
+
+        if let Some(np) = name_part {
+            query = query.filter(name.ilike(format!("%{}%", np)));
+        }
+        if let Some(sfx) = suffix {
+            query = query.filter(service_suffix.eq(sfx));
+        }
+        if let Some(m) = stub_method {
+            query = query.filter(method.eq(m));
+        }
+        if let Some(sc) = stub_scope {
+            query = query.filter(scope.eq(sc));
+        }
+
+        let res = query
+            .order(created.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(HttpStub::as_select())
+            .load(&mut conn)?;
+
+        Ok(res)
+    }
+
+    pub async fn update_stub(&self, stub_id: i32, upd_stub: NewHttpStub) -> Result<usize, Error> {
+        use crate::schema::stub::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = diesel::update(stub.find(stub_id))
+            .set(&upd_stub)
+            .execute(&mut conn)?;
+
+        Ok(res)
+    }
+
+    pub async fn delete_stub(&self, stub_id: i32) -> Result<usize, Error> {
+        use crate::schema::stub::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = diesel::delete(stub.find(stub_id)).execute(&mut conn)?;
+
+        Ok(res)
+    }
+
+    /// Finds live stubs with given method and either exact path or some path pattern.
+    /// Ephemeral stubs created before `ephemeral_since` are considered expired
+    pub async fn find_candidates(
+        &self,
+        req_method: HttpMethod,
+        req_path: &str,
+        ephemeral_since: DateTime<Utc>
+    ) -> Result<Vec<HttpStub>, Error> {
+        use crate::schema::stub::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = stub
+            .filter(method.eq(req_method))
+            .filter(path.eq(req_path).or(path_pattern.is_not_null()))
+            .filter(
+                scope.eq(Scope::Persistent)
+                    .or(scope.eq(Scope::Ephemeral).and(created.ge(ephemeral_since)))
+                    .or(scope.eq(Scope::Countdown).and(times.gt(0)))
+            )
+            .select(HttpStub::as_select())
+            .load(&mut conn)?;
+
+        Ok(res)
+    }
+
+    /// Loads all stubs regardless of their liveness
+    pub async fn find_all(&self) -> Result<Vec<HttpStub>, Error> {
+        use crate::schema::stub::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = stub.select(HttpStub::as_select()).load(&mut conn)?;
+
+        Ok(res)
+    }
+
+    /// Loads stubs with given ids, missing ids are skipped
+    pub async fn get_stubs(&self, stub_ids: &[i32]) -> Result<Vec<HttpStub>, Error> {
+        use crate::schema::stub::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = stub
+            .filter(id.eq_any(stub_ids))
+            .select(HttpStub::as_select())
+            .load(&mut conn)?;
+
+        Ok(res)
+    }
+
+    /// Finds stubs with the same method and scope, which may share the route: the ones with the same path
+    /// (or path pattern), and also path pattern stubs for an exact path and vice versa. Patterns are not matched here
+    pub async fn find_same_route(
+        &self,
+        stub_method: HttpMethod,
+        stub_scope: Scope,
+        stub_path: Option<&str>,
+        stub_path_pattern: Option<&str>
+    ) -> Result<Vec<HttpStub>, Error> {
+        use crate::schema::stub::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let mut query = stub
+            .filter(method.eq(stub_method))
+            .filter(scope.eq(stub_scope))
+            .into_boxed();
+
+        query = match (stub_path, stub_path_pattern) {
+            (Some(p), _) => query.filter(path.eq(p).or(path_pattern.is_not_null())),
+            (None, Some(pp)) => query.filter(path_pattern.eq(pp).or(path.is_not_null())),
+            (None, None) => return Ok(vec![])
+        };
+
+        let res = query.select(HttpStub::as_select()).load(&mut conn)?;
+
+        Ok(res)
+    }
+
+    /// Finds ids of the stubs responding with the given blob
+    pub async fn find_blob_users(&self, blob: i32) -> Result<Vec<i32>, Error> {
+        use crate::schema::stub::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = stub
+            .filter(response.retrieve_as_text("mode").eq("file"))
+            .filter(response.retrieve_as_text("blob_id").eq(blob.to_string()))
+            .select(id)
+            .load(&mut conn)?;
+
+        Ok(res)
+    }
+
+    /// Atomically decrements `times` of a countdown stub. Returns false if the stub is already exhausted
+    pub async fn decrement_times(&self, stub_id: i32) -> Result<bool, Error> {
+        use crate::schema::stub::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = diesel::update(stub.find(stub_id).filter(times.gt(0)))
+            .set(times.eq(times - 1))
             .execute(&mut conn)?;
 
+        Ok(res > 0)
+    }
+
+    /// Removes exhausted countdown stubs and ephemeral stubs created before `ephemeral_since`
+    pub async fn delete_expired(&self, ephemeral_since: DateTime<Utc>) -> Result<usize, Error> {
+        use crate::schema::stub::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = diesel::delete(stub.filter(
+            scope.eq(Scope::Ephemeral).and(created.lt(ephemeral_since))
+                .or(scope.eq(Scope::Countdown).and(times.le(0).or(times.is_null())))
+        )).execute(&mut conn)?;
+
         Ok(res)
     }
 }
@@ -46,7 +246,7 @@ impl StateDao {
         StateDao { pool }
     }
 
-    pub async fn create_state(&self, state_data: Value) -> Result<usize, Error> {
+    pub async fn create_state(&self, state_data: Value) -> Result<State, Error> {
         use crate::schema::state::dsl::*;
 
         let mut conn = self.pool.get()?;
@@ -55,19 +255,35 @@ impl StateDao {
 
         let res = diesel::insert_into(state)
             .values(&new_state)
-            .execute(&mut conn)?;
+            .returning(State::as_returning())
+            .get_result(&mut conn)?;
+
+        Ok(res)
+    }
+
+    pub async fn update_state(&self, state_id: i32, state_data: Value) -> Result<State, Error> {
+        use crate::schema::state::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = diesel::update(state.find(state_id))
+            .set(data.eq(state_data))
+            .returning(State::as_returning())
+            .get_result(&mut conn)?;
 
         Ok(res)
     }
 
-    pub async fn find_by_spec(&self, spec: HashMap<JsonOptic, HashMap<SqlKeyword, Value>>) -> Result<Vec<State>, Error> {
+    pub async fn find_by_spec(&self, spec: StateSpec) -> Result<Vec<State>, Error> {
         use crate::schema::state::dsl::*;
 
         let mut conn = self.pool.get()?;
 
         let mut query = state.into_boxed();
 
-        let predicates = spec.into_iter().map(|(optic, spec)| Predicate::from(optic, spec)).collect::<Vec<_>>();
+        let predicates = spec.conditions.into_iter().map(|(optic, spec)| Predicate::from(optic, spec))
+            .chain(spec.groups.into_iter().map(Predicate::from_group))
+            .collect::<Vec<_>>();
 
         for pred in predicates {
             query = query.filter(data.exists(pred.into_sql::<JsonPath>()));
@@ -77,6 +293,119 @@ impl StateDao {
 
         let res = query.load(&mut conn)?;
 
+        Ok(res)
+    }
+}
+
+#[derive(Clone)]
+pub struct ServiceDao {
+    pool: Pool<ConnectionManager<PgConnection>>
+}
+
+impl ServiceDao {
+    pub fn new(pool: Pool<ConnectionManager<PgConnection>>) -> ServiceDao {
+        ServiceDao { pool }
+    }
+
+    pub async fn insert_service(&self, new_service: Service) -> Result<Service, Error> {
+        use crate::schema::service::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = diesel::insert_into(service)
+            .values(&new_service)
+            .returning(Service::as_returning())
+            .get_result(&mut conn)?;
+
+        Ok(res)
+    }
+
+    pub async fn get_service(&self, service_suffix: &str) -> Result<Option<Service>, Error> {
+        use crate::schema::service::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = service
+            .find(service_suffix)
+            .select(Service::as_select())
+            .first(&mut conn)
+            .optional()?;
+
+        Ok(res)
+    }
+
+    pub async fn list_services(&self) -> Result<Vec<Service>, Error> {
+        use crate::schema::service::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = service
+            .order(suffix.asc())
+            .select(Service::as_select())
+            .load(&mut conn)?;
+
+        Ok(res)
+    }
+}
+
+#[derive(Clone)]
+pub struct BlobDao {
+    pool: Pool<ConnectionManager<PgConnection>>
+}
+
+impl BlobDao {
+    pub fn new(pool: Pool<ConnectionManager<PgConnection>>) -> BlobDao {
+        BlobDao { pool }
+    }
+
+    pub async fn insert_blob(&self, new_blob: NewBlob) -> Result<i32, Error> {
+        use crate::schema::blob::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = diesel::insert_into(blob)
+            .values(&new_blob)
+            .returning(id)
+            .get_result(&mut conn)?;
+
+        Ok(res)
+    }
+
+    pub async fn get_blob(&self, blob_id: i32) -> Result<Option<Blob>, Error> {
+        use crate::schema::blob::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = blob
+            .find(blob_id)
+            .select(Blob::as_select())
+            .first(&mut conn)
+            .optional()?;
+
+        Ok(res)
+    }
+
+    /// Lists blobs without their content, newest first
+    pub async fn list_blobs(&self) -> Result<Vec<BlobInfo>, Error> {
+        use crate::schema::blob::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = blob
+            .order(created.desc())
+            .select((id, created, name, content_type, sql::<Int4>("octet_length(content)")))
+            .load(&mut conn)?;
+
+        Ok(res)
+    }
+
+    pub async fn delete_blob(&self, blob_id: i32) -> Result<usize, Error> {
+        use crate::schema::blob::dsl::*;
+
+        let mut conn = self.pool.get()?;
+
+        let res = diesel::delete(blob.find(blob_id)).execute(&mut conn)?;
+
         Ok(res)
     }
 }
\ No newline at end of file
@@ -0,0 +1,283 @@
+use crate::dal::StubDao;
+use crate::error::Error;
+use crate::model::{HttpMethod, Scope};
+use crate::model::persistent::{path_regex, HttpStub};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Stub whose route matches the request path, along with named groups of its path pattern, available as `pathParts`
+pub type Matched = (Arc<HttpStub>, Value);
+
+/// In-process index of the stubs by method and path, path pattern stubs are grouped by method.
+/// It is kept coherent with the database by [crate::listener::StubChangeListener], while stubs changed
+/// by this instance are refreshed right away. The database is queried only if the index has no candidates for the request
+#[derive(Clone)]
+pub struct StubCache {
+    stub_dao: StubDao,
+    index: Arc<RwLock<Option<StubIndex>>>
+}
+
+impl StubCache {
+    pub fn new(stub_dao: StubDao) -> StubCache {
+        StubCache { stub_dao, index: Arc::new(RwLock::new(None)) }
+    }
+
+    /// Replaces the whole index with the stubs from the database. Returns the number of loaded stubs
+    pub async fn reload(&self) -> Result<usize, Error> {
+        let stubs = self.stub_dao.find_all().await?;
+        let loaded = stubs.len();
+
+        let mut index = StubIndex::default();
+        stubs.into_iter().for_each(|stub| index.insert(stub));
+
+        *self.index.write().unwrap_or_else(PoisonError::into_inner) = Some(index);
+
+        Ok(loaded)
+    }
+
+    /// Drops the index until the next reload, so that all stubs are looked up in the database
+    pub fn invalidate(&self) {
+        *self.index.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// Re-reads changed stubs, the ones missing in the database are dropped from the index
+    pub async fn refresh(&self, stub_ids: &[i32]) -> Result<(), Error> {
+        let stubs = self.stub_dao.get_stubs(stub_ids).await?;
+
+        if let Some(index) = self.index.write().unwrap_or_else(PoisonError::into_inner).as_mut() {
+            stub_ids.iter().for_each(|stub_id| index.remove(*stub_id));
+            stubs.into_iter().for_each(|stub| index.insert(stub));
+        }
+
+        Ok(())
+    }
+
+    /// Same as [StubDao::find_candidates], but path patterns are matched against `req_path`.
+    /// Patterns are compiled only for the stubs which come from the database
+    pub async fn find_candidates(
+        &self,
+        req_method: HttpMethod,
+        req_path: &str,
+        ephemeral_since: DateTime<Utc>
+    ) -> Result<Vec<Matched>, Error> {
+        let cached = self.index.read().unwrap_or_else(PoisonError::into_inner).as_ref()
+            .map(|index| index.find(req_method, req_path, ephemeral_since))
+            .unwrap_or_default();
+
+        // stub may be created on another instance, which hasn't notified this one yet
+        if !cached.is_empty() {
+            return Ok(cached);
+        }
+
+        let found = self.stub_dao.find_candidates(req_method, req_path, ephemeral_since).await?;
+
+        Ok(found.into_iter()
+            .filter_map(|stub| match_path(&stub, req_path).map(|path_parts| (Arc::new(stub), path_parts)))
+            .collect())
+    }
+}
+
+#[derive(Default)]
+struct StubIndex {
+    by_id: HashMap<i32, Arc<HttpStub>>,
+    by_path: HashMap<HttpMethod, HashMap<String, Vec<Arc<HttpStub>>>>,
+    by_pattern: HashMap<HttpMethod, Vec<(Regex, Arc<HttpStub>)>>
+}
+
+impl StubIndex {
+    /// Stubs without a path and with an invalid path pattern never match, so they are not indexed
+    fn insert(&mut self, stub: HttpStub) {
+        let stub = Arc::new(stub);
+
+        match (&stub.path, &stub.path_pattern) {
+            (Some(path), _) => self.by_path.entry(stub.method).or_default()
+                .entry(path.clone()).or_default()
+                .push(stub.clone()),
+            (None, Some(pattern)) => match path_regex(pattern) {
+                Ok(rx) => self.by_pattern.entry(stub.method).or_default().push((rx, stub.clone())),
+                Err(_) => return
+            },
+            (None, None) => return
+        }
+
+        self.by_id.insert(stub.id, stub);
+    }
+
+    fn remove(&mut self, stub_id: i32) {
+        let stub = match self.by_id.remove(&stub_id) {
+            Some(stub) => stub,
+            None => return
+        };
+
+        if let Some(path) = &stub.path {
+            if let Some(paths) = self.by_path.get_mut(&stub.method) {
+                if let Some(stubs) = paths.get_mut(path) {
+                    stubs.retain(|s| s.id != stub_id);
+
+                    if stubs.is_empty() {
+                        paths.remove(path);
+                    }
+                }
+            }
+        } else if let Some(patterns) = self.by_pattern.get_mut(&stub.method) {
+            patterns.retain(|(_, s)| s.id != stub_id);
+        }
+    }
+
+    /// Finds live stubs with the exact path or a matching path pattern
+    fn find(&self, method: HttpMethod, path: &str, ephemeral_since: DateTime<Utc>) -> Vec<Matched> {
+        let exact = self.by_path.get(&method)
+            .and_then(|paths| paths.get(path))
+            .into_iter()
+            .flatten()
+            .map(|stub| (stub.clone(), Value::Object(Map::new())));
+        let patterned = self.by_pattern.get(&method)
+            .into_iter()
+            .flatten()
+            .filter_map(|(rx, stub)| path_parts(rx, path).map(|path_parts| (stub.clone(), path_parts)));
+
+        exact.chain(patterned)
+            .filter(|(stub, _)| is_live(stub, ephemeral_since))
+            .collect()
+    }
+}
+
+/// Checks that request path matches the stub route, see [path_parts]
+fn match_path(stub: &HttpStub, path: &str) -> Option<Value> {
+    match (&stub.path, &stub.path_pattern) {
+        (Some(stub_path), _) if stub_path == path => Some(Value::Object(Map::new())),
+        (Some(_), _) => None,
+        (None, Some(pattern)) => path_parts(&path_regex(pattern).ok()?, path),
+        (None, None) => None
+    }
+}
+
+/// Returns named groups of the path pattern if it matches the request path
+fn path_parts(rx: &Regex, path: &str) -> Option<Value> {
+    let caps = rx.captures(path)?;
+
+    Some(Value::Object(
+        rx.capture_names()
+            .flatten()
+            .filter_map(|name| caps.name(name).map(|m| (name.to_string(), Value::String(m.as_str().to_string()))))
+            .collect::<Map<_, _>>()
+    ))
+}
+
+/// Mirrors the liveness filter of [StubDao::find_candidates]
+fn is_live(stub: &HttpStub, ephemeral_since: DateTime<Utc>) -> bool {
+    match stub.scope {
+        Scope::Persistent => true,
+        Scope::Ephemeral => stub.created >= ephemeral_since,
+        Scope::Countdown => stub.times.is_some_and(|times| times > 0)
+    }
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use crate::dal::cache::{match_path, StubIndex};
+    use crate::model::{HttpMethod, Scope};
+    use crate::model::persistent::HttpStub;
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+
+    fn found_ids(index: &StubIndex, method: HttpMethod, path: &str) -> Vec<i32> {
+        let mut ids = index.find(method, path, Utc::now() - Duration::hours(1)).iter().map(|(stub, _)| stub.id).collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn index_should_find_stubs_by_method_and_path() {
+        let mut index = StubIndex::default();
+        index.insert(HttpStub::at_route(1, HttpMethod::Get, Some("/alpha/orders"), None));
+        index.insert(HttpStub::at_route(2, HttpMethod::Post, Some("/alpha/orders"), None));
+        index.insert(HttpStub::at_route(3, HttpMethod::Get, None, Some(r"/alpha/orders/(?P<id>\d+)")));
+        index.insert(HttpStub::at_route(4, HttpMethod::Get, None, Some(r"/alpha/\w+")));
+        index.insert(HttpStub::at_route(5, HttpMethod::Get, None, Some(r"/alpha/(")));
+
+        assert_eq!(found_ids(&index, HttpMethod::Get, "/alpha/orders"), vec![1, 4]);
+        assert_eq!(found_ids(&index, HttpMethod::Post, "/alpha/orders"), vec![2]);
+        assert_eq!(found_ids(&index, HttpMethod::Get, "/alpha/orders/42"), vec![3]);
+        assert_eq!(found_ids(&index, HttpMethod::Get, "/alpha/orders/42/items"), Vec::<i32>::new());
+        assert_eq!(found_ids(&index, HttpMethod::Delete, "/alpha/orders"), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn index_should_extract_path_parts() {
+        let mut index = StubIndex::default();
+        index.insert(HttpStub::at_route(1, HttpMethod::Get, Some("/alpha/orders/42"), None));
+        index.insert(HttpStub::at_route(2, HttpMethod::Get, None, Some(r"/alpha/orders/(?P<id>\d+)")));
+
+        let mut found = index.find(HttpMethod::Get, "/alpha/orders/42", Utc::now())
+            .into_iter()
+            .map(|(stub, path_parts)| (stub.id, path_parts))
+            .collect::<Vec<_>>();
+        found.sort_by_key(|(id, _)| *id);
+
+        assert_eq!(found, vec![(1, json!({})), (2, json!({"id": "42"}))]);
+    }
+
+    #[test]
+    fn exact_path_should_match_without_path_parts() {
+        let stub = HttpStub::at_route(1, HttpMethod::Get, Some("/alpha/handler"), None);
+
+        assert_eq!(match_path(&stub, "/alpha/handler"), Some(json!({})));
+        assert_eq!(match_path(&stub, "/alpha/handler/1"), None);
+    }
+
+    #[test]
+    fn path_pattern_should_match_whole_path_and_extract_named_groups() {
+        let stub = HttpStub::at_route(1, HttpMethod::Get, None, Some(r"/alpha/(?P<user>\w+)/orders/(?P<order>\d+)"));
+
+        assert_eq!(match_path(&stub, "/alpha/peka/orders/42"), Some(json!({"user": "peka", "order": "42"})));
+        assert_eq!(match_path(&stub, "/alpha/peka/orders/42/items"), None);
+        assert_eq!(match_path(&stub, "/beta/alpha/peka/orders/42"), None);
+    }
+
+    #[test]
+    fn index_should_skip_expired_stubs() {
+        let mut ephemeral = HttpStub::at_route(1, HttpMethod::Get, Some("/alpha/orders"), None);
+        ephemeral.scope = Scope::Ephemeral;
+        ephemeral.created = Utc::now() - Duration::hours(2);
+
+        let mut exhausted = HttpStub::at_route(2, HttpMethod::Get, Some("/alpha/orders"), None);
+        exhausted.scope = Scope::Countdown;
+        exhausted.times = Some(0);
+
+        let mut countdown = HttpStub::at_route(3, HttpMethod::Get, Some("/alpha/orders"), None);
+        countdown.scope = Scope::Countdown;
+        countdown.times = Some(1);
+
+        let mut index = StubIndex::default();
+        index.insert(ephemeral);
+        index.insert(exhausted);
+        index.insert(countdown);
+
+        assert_eq!(found_ids(&index, HttpMethod::Get, "/alpha/orders"), vec![3]);
+    }
+
+    #[test]
+    fn removed_stubs_should_not_be_found() {
+        let mut index = StubIndex::default();
+        index.insert(HttpStub::at_route(1, HttpMethod::Get, Some("/alpha/orders"), None));
+        index.insert(HttpStub::at_route(2, HttpMethod::Get, Some("/alpha/orders"), None));
+        index.insert(HttpStub::at_route(3, HttpMethod::Get, None, Some(r"/alpha/.+")));
+
+        index.remove(2);
+        index.remove(3);
+        index.remove(42);
+
+        assert_eq!(found_ids(&index, HttpMethod::Get, "/alpha/orders"), vec![1]);
+
+        // updated stub is removed and inserted again
+        index.remove(1);
+        index.insert(HttpStub::at_route(1, HttpMethod::Get, Some("/alpha/users"), None));
+
+        assert_eq!(found_ids(&index, HttpMethod::Get, "/alpha/orders"), Vec::<i32>::new());
+        assert_eq!(found_ids(&index, HttpMethod::Get, "/alpha/users"), vec![1]);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::error::Error;
+use diesel::ConnectionError;
 use diesel::r2d2::Error as DieselR2D2Error;
 use diesel::result::Error as DieselError;
 use r2d2::Error as R2D2Error;
@@ -19,4 +20,10 @@ impl From<R2D2Error> for Error {
     fn from(value: R2D2Error) -> Self {
         Error::from(value)
     }
+}
+
+impl From<ConnectionError> for Error {
+    fn from(value: ConnectionError) -> Self {
+        Error::from(value)
+    }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use crate::error::Error;
-use crate::model::sql_json::Keyword as SqlKeyword;
+use crate::model::sql_json::{Keyword as SqlKeyword, StateSpec};
+use crate::predicate_dsl::group::Group;
 use crate::utils::js::Jsn;
 use crate::utils::js::optic::JsonOptic;
 use diesel::{AppearsOnTable, Expression, SqlType, QueryResult, infix_operator};
@@ -49,15 +50,43 @@ pub trait JsonbQueryMethods
 
 impl<T: Expression<SqlType = Jsonb>> JsonbQueryMethods for T {}
 
+/// JSON path query. Conditions on a single optic are rendered as filters on that optic,
+/// groups become a filter on the root: `$ ? (exists(...) || !(exists(...)))`
 #[derive(Debug)]
-pub struct Predicate(JsonOptic, HashMap<SqlKeyword, Jsn>);
+pub enum Predicate {
+    Conditions(JsonOptic, HashMap<SqlKeyword, Jsn>),
+    Or(Vec<Predicate>),
+    And(Vec<Predicate>),
+    Not(Box<Predicate>)
+}
 
 impl Predicate {
     pub fn from(optic: JsonOptic, spec: HashMap<SqlKeyword, Value>) -> Predicate {
-        Predicate(optic, spec.into_iter()
+        Predicate::Conditions(optic, spec.into_iter()
             .map(|(kx, vx)| (kx, Into::<Jsn>::into(vx)))
             .collect::<HashMap<_, _>>())
     }
+
+    pub fn from_group(group: Group<StateSpec>) -> Predicate {
+        match group {
+            Group::Or(specs) => Predicate::Or(specs.into_iter().map(Predicate::from_spec).collect()),
+            Group::And(specs) => Predicate::And(specs.into_iter().map(Predicate::from_spec).collect()),
+            Group::Not(spec) => Predicate::Not(Box::new(Predicate::from_spec(*spec)))
+        }
+    }
+
+    /// All conditions and groups of the spec should hold
+    pub fn from_spec(spec: StateSpec) -> Predicate {
+        let mut preds = spec.conditions.into_iter().map(|(optic, conds)| Predicate::from(optic, conds))
+            .chain(spec.groups.into_iter().map(Predicate::from_group))
+            .collect::<Vec<_>>();
+
+        if preds.len() == 1 {
+            preds.remove(0)
+        } else {
+            Predicate::And(preds)
+        }
+    }
 }
 
 impl QueryId for Predicate {
@@ -78,52 +107,18 @@ impl<QS> AppearsOnTable<QS> for Predicate where Self: Expression {}
 impl QueryFragment<Pg> for Predicate {
     fn walk_ast<'b>(&'b self, mut pass: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
         pass.push_sql("format('");
-        pass.push_sql(self.0.to_json_path_string().as_str());
 
         let mut args: Vec<&Jsn> = vec![];
 
-        for (kwd, val) in self.1.iter() {
-            match kwd {
-                SqlKeyword::Eq => {
-                    pass.push_sql(" ?(@ == %s)");
-                    args.push(val);
-                }
-                SqlKeyword::NotEq => {
-                    pass.push_sql(" ?(@ != %s)");
-                    args.push(val);
-                }
-                SqlKeyword::Less => {
-                    pass.push_sql(" ?(@ < %s)");
-                    args.push(val);
-                }
-                SqlKeyword::Lte => {
-                    pass.push_sql(" ?(@ <= %s)");
-                    args.push(val);
-                }
-                SqlKeyword::Greater => {
-                    pass.push_sql(" ?(@ > %s)");
-                    args.push(val);
-                }
-                SqlKeyword::Gte => {
-                    pass.push_sql(" ?(@ >= %s)");
-                    args.push(val);
-                }
-                SqlKeyword::Rx => {
-                    if !val.is_string() {
-                        return Err(query_builder_error("Incorrect argument for 'like_regex'"))
-                    }
-                    pass.push_sql(" ?(@ like_regex %s)");
-                    args.push(val);
-                }
-                SqlKeyword::StartsWith => {
-                    if !val.is_string() {
-                        return Err(query_builder_error("Incorrect argument for 'starts with'"))
-                    }
-                    pass.push_sql(" ?(@ starts with %s)");
-                    args.push(val);
-                }
+        match self {
+            Predicate::Conditions(optic, conds) => push_conditions(&mut pass, optic, conds, &mut args)?,
+            group => {
+                pass.push_sql("$ ? (");
+                push_boolean(&mut pass, group, &mut args)?;
+                pass.push_sql(")");
             }
         }
+
         pass.push_sql("'");
 
         for arg in args {
@@ -144,6 +139,87 @@ impl QueryFragment<Pg> for Predicate {
     }
 }
 
+/// Renders predicate as a boolean expression, conditions on optics are checked with `exists`.
+/// Empty `And` is rendered as always true `exists($)`
+fn push_boolean<'b>(pass: &mut AstPass<'_, 'b, Pg>, predicate: &'b Predicate, args: &mut Vec<&'b Jsn>) -> QueryResult<()> {
+    match predicate {
+        Predicate::Conditions(optic, conds) => {
+            pass.push_sql("exists(");
+            push_conditions(pass, optic, conds, args)?;
+            pass.push_sql(")");
+        }
+        Predicate::And(preds) if preds.is_empty() => pass.push_sql("exists($)"),
+        Predicate::Or(preds) | Predicate::And(preds) => {
+            let operator = if matches!(predicate, Predicate::Or(_)) { " || " } else { " && " };
+
+            pass.push_sql("(");
+            for (idx, pred) in preds.iter().enumerate() {
+                if idx > 0 {
+                    pass.push_sql(operator);
+                }
+                push_boolean(pass, pred, args)?;
+            }
+            pass.push_sql(")");
+        }
+        Predicate::Not(pred) => {
+            pass.push_sql("!(");
+            push_boolean(pass, pred, args)?;
+            pass.push_sql(")");
+        }
+    }
+
+    Ok(())
+}
+
+fn push_conditions<'b>(pass: &mut AstPass<'_, 'b, Pg>, optic: &JsonOptic, conds: &'b HashMap<SqlKeyword, Jsn>, args: &mut Vec<&'b Jsn>) -> QueryResult<()> {
+    pass.push_sql(optic.to_json_path_string().as_str());
+
+    for (kwd, val) in conds.iter() {
+        match kwd {
+            SqlKeyword::Eq => {
+                pass.push_sql(" ?(@ == %s)");
+                args.push(val);
+            }
+            SqlKeyword::NotEq => {
+                pass.push_sql(" ?(@ != %s)");
+                args.push(val);
+            }
+            SqlKeyword::Less => {
+                pass.push_sql(" ?(@ < %s)");
+                args.push(val);
+            }
+            SqlKeyword::Lte => {
+                pass.push_sql(" ?(@ <= %s)");
+                args.push(val);
+            }
+            SqlKeyword::Greater => {
+                pass.push_sql(" ?(@ > %s)");
+                args.push(val);
+            }
+            SqlKeyword::Gte => {
+                pass.push_sql(" ?(@ >= %s)");
+                args.push(val);
+            }
+            SqlKeyword::Rx => {
+                if !val.is_string() {
+                    return Err(query_builder_error("Incorrect argument for 'like_regex'"))
+                }
+                pass.push_sql(" ?(@ like_regex %s)");
+                args.push(val);
+            }
+            SqlKeyword::StartsWith => {
+                if !val.is_string() {
+                    return Err(query_builder_error("Incorrect argument for 'starts with'"))
+                }
+                pass.push_sql(" ?(@ starts with %s)");
+                args.push(val);
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn query_builder_error(msg: &str) -> DieselError {
     DieselError::QueryBuilderError(Box::new(Error::new(msg.to_string())))
 }
@@ -164,7 +240,7 @@ fn push_json_value<'a, 'b>(mut pass: AstPass<'a, 'b, Pg>, json_value: &'b Jsn) -
 #[cfg(test)]
 mod jsonb_tests {
     use crate::dal::jsonb::{JsonPath, JsonbQueryMethods, Predicate};
-    use crate::model::sql_json::Keyword;
+    use crate::model::sql_json::{Keyword, StateSpec};
     use crate::schema::state::dsl::*;
     use crate::utils::js::optic::JsonOptic;
     use diesel::prelude::*;
@@ -236,4 +312,23 @@ mod jsonb_tests {
         let sql = debug_query::<Pg, _>(&state.filter(&data.exists((&Predicate::from(optic, spec)).into_sql::<JsonPath>()))).to_string();
         assert_eq!(sql, r#"SELECT "state"."id", "state"."created", "state"."data" FROM "state" WHERE "state"."data" @? format('$.a.b ?(@ starts with %s)', to_json($1::text))::jsonpath -- binds: ["test"]"#)
     }
+
+    #[test]
+    fn check_groups_spec_sql() {
+        let spec = serde_json::from_value::<StateSpec>(json!({
+            "$or": [{"a": {"==": 1}}, {"b": {"^": "x"}, "$not": {"c": {">": 2}}}]
+        })).ok().unwrap();
+        let pred = Predicate::from_group(spec.groups.into_iter().next().unwrap());
+        let sql = debug_query::<Pg, _>(&state.filter(&data.exists((&pred).into_sql::<JsonPath>()))).to_string();
+        assert_eq!(sql, r#"SELECT "state"."id", "state"."created", "state"."data" FROM "state" WHERE "state"."data" @? format('$ ? ((exists($.a ?(@ == %s)) || (exists($.b ?(@ starts with %s)) && !(exists($.c ?(@ > %s))))))', to_json($1), to_json($2::text), to_json($3))::jsonpath -- binds: [1, "x", 2]"#)
+    }
+
+    #[test]
+    fn flat_spec_should_have_no_groups() {
+        let spec = serde_json::from_value::<StateSpec>(json!({"a.b": {"==": 42}})).ok().unwrap();
+
+        assert!(spec.groups.is_empty());
+        assert_eq!(spec.conditions, HashMap::from([(JsonOptic::from_path("a.b"), HashMap::from([(Keyword::Eq, json!(42))]))]));
+        assert_eq!(serde_json::to_value(&spec).unwrap(), json!({"a.b": {"==": 42}}));
+    }
 }
\ No newline at end of file
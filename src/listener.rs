@@ -0,0 +1,135 @@
+use crate::dal::cache::StubCache;
+use crate::error::Error;
+use actix_web::rt;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use log::{error, info};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Channel notified by the `stub_changed` trigger, the payload is the id of the changed stub
+const CHANNEL: &str = "stub_changes";
+
+/// Delays between attempts to reconnect, doubled after each failed attempt
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Keeps [StubCache] coherent with the database by periodically polling stub change notifications.
+/// The cache is not used while the notifications can't be received
+pub struct StubChangeListener {
+    stub_cache: StubCache,
+    db_uri: String,
+    interval: Duration
+}
+
+impl StubChangeListener {
+    pub fn new(stub_cache: StubCache, db_uri: String, interval: Duration) -> StubChangeListener {
+        StubChangeListener { stub_cache, db_uri, interval }
+    }
+
+    /// Runs on a dedicated thread, since database calls are blocking. Returns after the first attempt
+    /// to load the cache, so that stubs are served from memory right away
+    pub fn spawn(self) {
+        let (attempted_tx, attempted_rx) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("stub-change-listener".to_string())
+            .spawn(move || rt::System::new().block_on(self.run(attempted_tx)))
+            .expect("Could not start stub change listener");
+
+        let _ = attempted_rx.recv();
+    }
+
+    async fn run(self, attempted: mpsc::Sender<()>) {
+        let mut attempted = Some(attempted);
+        let mut backoff = MIN_BACKOFF;
+
+        loop {
+            let conn = self.listen().await;
+
+            if let Some(attempted) = attempted.take() {
+                let _ = attempted.send(());
+            }
+
+            match conn {
+                Some(conn) => {
+                    backoff = MIN_BACKOFF;
+                    self.poll(conn).await;
+                }
+                None => {
+                    rt::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+    }
+
+    /// Subscribes to the notifications and reloads the whole cache, since changes could be missed before
+    async fn listen(&self) -> Option<PgConnection> {
+        let subscribed = async {
+            let mut conn = PgConnection::establish(&self.db_uri)?;
+            diesel::sql_query(format!("LISTEN {}", CHANNEL)).execute(&mut conn)?;
+
+            let loaded = self.stub_cache.reload().await?;
+
+            Ok::<_, Error>((conn, loaded))
+        };
+
+        match subscribed.await {
+            Ok((conn, loaded)) => {
+                info!("Loaded {} stubs into cache", loaded);
+                Some(conn)
+            }
+            Err(e) => {
+                error!("Failed to listen to stub changes: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Applies the changes until the notifications are lost, the cache is not used afterwards
+    async fn poll(&self, mut conn: PgConnection) {
+        let mut ticker = rt::time::interval(self.interval);
+
+        loop {
+            ticker.tick().await;
+
+            let changed = match received_ids(&mut conn) {
+                Ok(changed) if changed.is_empty() => continue,
+                Ok(changed) => changed,
+                Err(e) => {
+                    error!("Lost stub change notifications: {}", e);
+                    break;
+                }
+            };
+
+            // changes are lost if the stubs can't be re-read, so the whole cache is reloaded later
+            if let Err(e) = self.stub_cache.refresh(&changed).await {
+                error!("Failed to refresh stub cache: {}", e);
+                break;
+            }
+        }
+
+        self.stub_cache.invalidate();
+    }
+}
+
+/// Collects ids of the stubs from the notifications received so far, doesn't wait for new ones
+fn received_ids(conn: &mut PgConnection) -> Result<Vec<i32>, Error> {
+    let mut ids = vec![];
+
+    for notification in conn.notifications_iter() {
+        let notification = notification?;
+
+        match notification.payload.parse::<i32>() {
+            Ok(id) if notification.channel == CHANNEL => ids.push(id),
+            _ => ()
+        }
+    }
+
+    ids.sort();
+    ids.dedup();
+
+    Ok(ids)
+}
@@ -1,6 +1,15 @@
 use crate::api::admin::AdminApiHandler;
+use crate::api::callback::{CallbackEngine, RetryPolicy};
+use crate::api::persister::StatePersister;
+use crate::api::proxy::Proxy;
+use crate::api::public::PublicApiHandler;
+use crate::api::resolver::StubResolver;
+use crate::cleaner::EphemeralCleaner;
 use crate::dal::*;
+use crate::dal::cache::StubCache;
+use crate::listener::StubChangeListener;
 use actix_web::{App, HttpServer, web};
+use chrono::Duration;
 use diesel::PgConnection;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
@@ -11,8 +20,10 @@ use std::env;
 extern crate diesel_autoincrement_new_struct;
 
 pub mod api;
+pub mod cleaner;
 pub mod dal;
 pub mod error;
+pub mod listener;
 pub mod model;
 pub mod predicate_dsl;
 pub mod schema;
@@ -21,9 +32,19 @@ pub mod utils;
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().expect(".env file not found");
+    env_logger::init();
 
     let db_uri = env::var("DATABASE_URL").expect("Database url not defined");
-    let manager = ConnectionManager::<PgConnection>::new(db_uri);
+    let ephemeral_ttl = Duration::seconds(env_number("EPHEMERAL_TTL_SECS", 86400));
+    let cleanup_interval = std::time::Duration::from_secs(env_number("CLEANUP_INTERVAL_SECS", 60));
+    let callback_retry_policy = RetryPolicy {
+        attempts: env_number("CALLBACK_ATTEMPTS", 3),
+        backoff: std::time::Duration::from_millis(env_number("CALLBACK_BACKOFF_MILLIS", 1000))
+    };
+    let cache_poll_interval = std::time::Duration::from_millis(env_number("CACHE_POLL_MILLIS", 50));
+    let max_payload_bytes: usize = env_number("MAX_PAYLOAD_BYTES", 16 * 1024 * 1024);
+
+    let manager = ConnectionManager::<PgConnection>::new(db_uri.clone());
     let pool = Pool::builder()
         .test_on_check_out(true)
         .build(manager)
@@ -31,18 +52,47 @@ async fn main() -> std::io::Result<()> {
 
     let stub_dao = StubDao::new(pool.clone());
     let state_dao = StateDao::new(pool.clone());
+    let service_dao = ServiceDao::new(pool.clone());
+    let blob_dao = BlobDao::new(pool.clone());
+
+    EphemeralCleaner::new(stub_dao.clone(), ephemeral_ttl, cleanup_interval).spawn();
+
+    let stub_cache = StubCache::new(stub_dao.clone());
+    StubChangeListener::new(stub_cache.clone(), db_uri, cache_poll_interval).spawn();
 
-    let admin_api_handler = AdminApiHandler::new(stub_dao, state_dao);
+    let stub_resolver = StubResolver::new(stub_cache.clone(), stub_dao.clone(), state_dao.clone(), ephemeral_ttl);
+
+    let state_persister = StatePersister::new(state_dao.clone());
+    let callback_engine = CallbackEngine::new(state_persister.clone(), callback_retry_policy);
+
+    let public_api_handler = PublicApiHandler::new(stub_resolver, state_persister, Proxy::new(), callback_engine, blob_dao.clone());
+    let admin_api_handler = AdminApiHandler::new(stub_dao, stub_cache, state_dao, service_dao, blob_dao);
 
     HttpServer::new(move || {
         App::new()
+            .app_data(web::Data::new(public_api_handler.clone()))
             .app_data(web::Data::new(admin_api_handler.clone()))
-            .service(api::exec_get)
-            .service(api::exec_post)
+            .app_data(web::PayloadConfig::new(max_payload_bytes))
+            .route(api::EXEC_PATH, web::route().to(api::exec))
             .service(api::fetch_states)
             .service(api::create_stub)
+            .service(api::get_stub)
+            .service(api::list_stubs)
+            .service(api::update_stub)
+            .service(api::delete_stub)
+            .service(api::create_service)
+            .service(api::list_services)
+            .service(api::upload_blob)
+            .service(api::list_blobs)
+            .service(api::delete_blob)
     })
         .bind(("127.0.0.1", 8080))?
         .run()
         .await
+}
+
+fn env_number<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name).ok()
+        .map(|v| v.parse::<T>().unwrap_or_else(|_| panic!("{} should be a number", name)))
+        .unwrap_or(default)
 }
\ No newline at end of file
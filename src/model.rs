@@ -1,10 +1,11 @@
 use diesel_derive_enum;
 use serde::{Deserialize, Serialize};
 
+pub mod delay;
 pub mod persistent;
 pub mod sql_json;
 
-#[derive(Debug, diesel_derive_enum::DbEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, diesel_derive_enum::DbEnum)]
 #[ExistingTypePath = "crate::schema::sql_types::Scope"]
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -14,7 +15,18 @@ pub enum Scope {
     Countdown
 }
 
-#[derive(Debug, diesel_derive_enum::DbEnum)]
+impl Scope {
+    /// Stubs with higher priority take precedence during resolution
+    pub fn priority(&self) -> u8 {
+        match self {
+            Scope::Countdown => 2,
+            Scope::Ephemeral => 1,
+            Scope::Persistent => 0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, diesel_derive_enum::DbEnum)]
 #[ExistingTypePath = "crate::schema::sql_types::HttpMethod"]
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
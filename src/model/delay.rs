@@ -0,0 +1,120 @@
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use serde_json::{Map, Value, json};
+use std::time::Duration;
+
+/// Response or callback delay. Accepts human-readable durations like `"150 millis"`
+/// or a range `{"min": "100 millis", "max": "300 millis"}` to pick a random delay from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Delay {
+    Fixed(Duration),
+    Range {
+        min: Duration,
+        max: Duration
+    }
+}
+
+impl Delay {
+    /// Picks an actual duration to wait
+    pub fn sample(&self) -> Duration {
+        match self {
+            Delay::Fixed(duration) => *duration,
+            Delay::Range { min, max } => rand::thread_rng().gen_range(*min..=*max)
+        }
+    }
+}
+
+impl Serialize for Delay {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        match self {
+            Delay::Fixed(duration) => serializer.serialize_str(&render_duration(duration)),
+            Delay::Range { min, max } => json!({"min": render_duration(min), "max": render_duration(max)}).serialize(serializer)
+        }
+    }
+}
+
+impl <'de> Deserialize<'de> for Delay {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        match Value::deserialize(deserializer)? {
+            Value::String(s) => parse_duration(&s).map(Delay::Fixed).map_err(D::Error::custom),
+            Value::Object(fields) if fields.contains_key("min") || fields.contains_key("max") => {
+                let min = range_bound(&fields, "min").map_err(D::Error::custom)?;
+                let max = range_bound(&fields, "max").map_err(D::Error::custom)?;
+
+                if min > max {
+                    Err(D::Error::custom("Delay 'min' should not exceed 'max'"))
+                } else {
+                    Ok(Delay::Range { min, max })
+                }
+            }
+            // {"secs": .., "nanos": ..} representation of std::time::Duration
+            other => serde_json::from_value::<Duration>(other).map(Delay::Fixed).map_err(D::Error::custom)
+        }
+    }
+}
+
+fn range_bound(fields: &Map<String, Value>, name: &str) -> Result<Duration, String> {
+    match fields.get(name) {
+        Some(Value::String(s)) => parse_duration(s),
+        _ => Err(format!("Delay range should have '{}' bound", name))
+    }
+}
+
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    humantime::parse_duration(s).map_err(|e| format!("Incorrect duration '{}': {}", s, e))
+}
+
+fn render_duration(duration: &Duration) -> String {
+    format!("{} millis", duration.as_millis())
+}
+
+#[cfg(test)]
+mod delay_tests {
+    use crate::model::delay::Delay;
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn human_readable_delay_should_be_parsed() {
+        assert_eq!(serde_json::from_value::<Delay>(json!("150 millis")).ok(), Some(Delay::Fixed(Duration::from_millis(150))));
+        assert_eq!(serde_json::from_value::<Delay>(json!("2 seconds")).ok(), Some(Delay::Fixed(Duration::from_secs(2))));
+        assert_eq!(serde_json::from_value::<Delay>(json!("1s 500ms")).ok(), Some(Delay::Fixed(Duration::from_millis(1500))));
+        assert!(serde_json::from_value::<Delay>(json!("soon")).is_err());
+    }
+
+    #[test]
+    fn legacy_delay_should_be_parsed() {
+        let delay = serde_json::from_value::<Delay>(json!({"secs": 1, "nanos": 0})).ok();
+
+        assert_eq!(delay, Some(Delay::Fixed(Duration::from_secs(1))));
+    }
+
+    #[test]
+    fn delay_range_should_be_parsed() {
+        let delay = serde_json::from_value::<Delay>(json!({"min": "100 millis", "max": "300 millis"})).ok();
+
+        assert_eq!(delay, Some(Delay::Range { min: Duration::from_millis(100), max: Duration::from_millis(300) }));
+        assert!(serde_json::from_value::<Delay>(json!({"min": "300 millis", "max": "100 millis"})).is_err());
+        assert!(serde_json::from_value::<Delay>(json!({"min": "300 millis"})).is_err());
+    }
+
+    #[test]
+    fn delay_should_be_sampled_within_range() {
+        let delay = Delay::Range { min: Duration::from_millis(100), max: Duration::from_millis(300) };
+
+        for _ in 0..100 {
+            let sampled = delay.sample();
+            assert!(sampled >= Duration::from_millis(100) && sampled <= Duration::from_millis(300));
+        }
+    }
+
+    #[test]
+    fn delay_should_be_serialized_in_human_readable_form() {
+        let fixed = Delay::Fixed(Duration::from_millis(150));
+        let range = Delay::Range { min: Duration::from_millis(100), max: Duration::from_secs(1) };
+
+        assert_eq!(serde_json::to_value(&fixed).ok(), Some(json!("150 millis")));
+        assert_eq!(serde_json::to_value(&range).ok(), Some(json!({"min": "100 millis", "max": "1000 millis"})));
+    }
+}
\ No newline at end of file
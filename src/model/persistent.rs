@@ -1,16 +1,21 @@
 use crate::model::*;
-use crate::model::sql_json::Keyword as SqlKeyword;
-use crate::predicate_dsl::json::JsonPredicate;
-use crate::predicate_dsl::keyword::Keyword;
+use crate::model::delay::Delay;
+use crate::model::sql_json::StateSpec;
+use crate::predicate_dsl::json::{ConditionProblem, JsonPredicate};
+use crate::predicate_dsl::xml::XmlPredicate;
+use crate::utils::form::{parse_multipart, urlencoded_to_json, Part};
 use crate::utils::js::optic::JsonOptic;
+use crate::utils::transformations::js::JsonTransformations;
+use crate::utils::xml::{canonicalize, deserialize_xml, render_xml, XmlDocument};
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel_autoincrement_new_struct::prelude::*;
 use diesel_json::Json;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::time::Duration;
+use sxd_document::parser;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "mode")]
@@ -18,33 +23,202 @@ pub enum HttpStubRequest {
     #[serde(rename = "no_body")]
     RequestWithoutBody {
         headers: HashMap<String, String>,
-        #[serde(default = "HashMap::new")]
-        query: HashMap<JsonOptic, HashMap<Keyword, Value>>
+        #[serde(default)]
+        query: JsonPredicate
     },
     #[serde(rename = "json")]
     JsonRequest {
         headers: HashMap<String, String>,
-        #[serde(default = "HashMap::new")]
-        query: HashMap<JsonOptic, HashMap<Keyword, Value>>,
+        #[serde(default)]
+        query: JsonPredicate,
         body: Value
     },
     #[serde(rename = "raw")]
     RawRequest {
         headers: HashMap<String, String>,
-        #[serde(default = "HashMap::new")]
-        query: HashMap<JsonOptic, HashMap<Keyword, Value>>,
+        #[serde(default)]
+        query: JsonPredicate,
         body: String
     },
     #[serde(rename = "jlens")]
     JLensRequest {
         headers: HashMap<String, String>,
-        #[serde(default = "HashMap::new")]
-        query: HashMap<JsonOptic, HashMap<Keyword, Value>>,
+        #[serde(default)]
+        query: JsonPredicate,
+        body: JsonPredicate
+    },
+    /// Body should be equal to the given XML document up to formatting and namespace prefixes
+    #[serde(rename = "xml")]
+    XmlRequest {
+        headers: HashMap<String, String>,
+        #[serde(default)]
+        query: JsonPredicate,
+        body: XmlDocument
+    },
+    #[serde(rename = "xpath")]
+    XPathRequest {
+        headers: HashMap<String, String>,
+        #[serde(default)]
+        query: JsonPredicate,
+        body: XmlPredicate,
+        /// Prefixes available in the XPath expressions
+        #[serde(default)]
+        #[serde(skip_serializing_if = "HashMap::is_empty")]
+        namespaces: HashMap<String, String>
+    },
+    /// Every described part should be present in `multipart/form-data` body, other parts are ignored
+    #[serde(rename = "multipart")]
+    MultipartRequest {
+        headers: HashMap<String, String>,
+        #[serde(default)]
+        query: JsonPredicate,
+        body: Vec<RequestPart>
+    },
+    /// Fields of `application/x-www-form-urlencoded` body are checked as a JSON object with string values
+    #[serde(rename = "form")]
+    FormRequest {
+        headers: HashMap<String, String>,
+        #[serde(default)]
+        query: JsonPredicate,
         body: JsonPredicate
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct RequestPart {
+    pub name: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    #[serde(flatten)]
+    pub body: PartBody
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "mode", content = "value")]
+pub enum PartBody {
+    #[serde(rename = "any")]
+    Any,
+    #[serde(rename = "raw")]
+    Raw(String),
+    #[serde(rename = "json")]
+    Json(Value),
+    #[serde(rename = "jlens")]
+    JLens(JsonPredicate),
+    #[serde(rename = "xml")]
+    Xml(XmlDocument)
+}
+
+impl RequestPart {
+    fn check(&self, part: &Part) -> bool {
+        let text = std::str::from_utf8(&part.body);
+
+        self.headers.iter().all(|(name, value)| part.headers.get(&name.to_lowercase()) == Some(value))
+            && match (&self.body, text) {
+                (PartBody::Any, _) => true,
+                (PartBody::Raw(etalon), Ok(text)) => text == etalon,
+                (PartBody::Json(etalon), Ok(text)) => serde_json::from_str::<Value>(text).map(|json| json == *etalon).unwrap_or(false),
+                (PartBody::JLens(predicate), Ok(text)) =>
+                    serde_json::from_str::<Value>(text).ok().and_then(|json| predicate.validate(json).ok()).unwrap_or(false),
+                (PartBody::Xml(etalon), Ok(text)) =>
+                    canonicalize(text).is_ok_and(|actual| actual == etalon.canonical),
+                (_, Err(_)) => false
+            }
+    }
+}
+
+impl HttpStubRequest {
+    pub fn headers(&self) -> &HashMap<String, String> {
+        match self {
+            HttpStubRequest::RequestWithoutBody { headers, .. } => headers,
+            HttpStubRequest::JsonRequest { headers, .. } => headers,
+            HttpStubRequest::RawRequest { headers, .. } => headers,
+            HttpStubRequest::JLensRequest { headers, .. } => headers,
+            HttpStubRequest::XmlRequest { headers, .. } => headers,
+            HttpStubRequest::XPathRequest { headers, .. } => headers,
+            HttpStubRequest::MultipartRequest { headers, .. } => headers,
+            HttpStubRequest::FormRequest { headers, .. } => headers
+        }
+    }
+
+    pub fn query(&self) -> &JsonPredicate {
+        match self {
+            HttpStubRequest::RequestWithoutBody { query, .. } => query,
+            HttpStubRequest::JsonRequest { query, .. } => query,
+            HttpStubRequest::RawRequest { query, .. } => query,
+            HttpStubRequest::JLensRequest { query, .. } => query,
+            HttpStubRequest::XmlRequest { query, .. } => query,
+            HttpStubRequest::XPathRequest { query, .. } => query,
+            HttpStubRequest::MultipartRequest { query, .. } => query,
+            HttpStubRequest::FormRequest { query, .. } => query
+        }
+    }
+
+    /// Lists faulty conditions of the predicates in a request specification
+    /// along with their locations, e.g. `body[0].value`
+    pub fn predicate_problems(spec: &Value) -> Vec<(String, ConditionProblem)> {
+        let mut problems = vec![];
+        let mut add = |location: String, found: Vec<ConditionProblem>| problems.extend(found.into_iter().map(|problem| (location.clone(), problem)));
+
+        if let Some(query) = spec.get("query") {
+            add("query".to_string(), JsonPredicate::problems(query));
+        }
+
+        match (spec.get("mode").and_then(Value::as_str), spec.get("body")) {
+            (Some("jlens" | "form"), Some(body)) => add("body".to_string(), JsonPredicate::problems(body)),
+            (Some("xpath"), Some(body)) => add("body".to_string(), XmlPredicate::problems(body)),
+            (Some("multipart"), Some(Value::Array(parts))) => {
+                for (idx, part) in parts.iter().enumerate() {
+                    if let (Some("jlens"), Some(value)) = (part.get("mode").and_then(Value::as_str), part.get("value")) {
+                        add(format!("body[{}].value", idx), JsonPredicate::problems(value));
+                    }
+                }
+            }
+            _ => ()
+        }
+
+        problems
+    }
+
+    /// Checks request headers: names are compared case-insensitively, values should match exactly.
+    /// Header names of the incoming request are expected to be lowercase
+    pub fn check_headers(&self, request_headers: &HashMap<String, String>) -> bool {
+        self.headers().iter().all(|(name, value)| request_headers.get(&name.to_lowercase()) == Some(value))
+    }
+
+    /// Checks query parameters, represented as a JSON object
+    pub fn check_query(&self, query: &Value) -> bool {
+        self.query().validate(query.clone()).unwrap_or(false)
+    }
+
+    /// Checks request body, `content_type` is required to split multipart bodies.
+    /// Bodies which are not valid UTF-8 can match only `no_body` and `multipart` stubs
+    pub fn check_body(&self, body: &[u8], content_type: Option<&str>) -> bool {
+        let text = std::str::from_utf8(body);
+
+        match (self, text) {
+            (HttpStubRequest::RequestWithoutBody { .. }, _) => true,
+            (HttpStubRequest::MultipartRequest { body: described, .. }, _) =>
+                content_type.and_then(|ct| parse_multipart(body, ct).ok())
+                    .map(|parts| described.iter().all(|dp| parts.iter().any(|part| part.name == dp.name && dp.check(part))))
+                    .unwrap_or(false),
+            (_, Err(_)) => false,
+            (HttpStubRequest::JsonRequest { body: etalon, .. }, Ok(body)) =>
+                serde_json::from_str::<Value>(body).map(|json| json == *etalon).unwrap_or(false),
+            (HttpStubRequest::RawRequest { body: etalon, .. }, Ok(body)) => body == etalon,
+            (HttpStubRequest::JLensRequest { body: predicate, .. }, Ok(body)) =>
+                serde_json::from_str::<Value>(body).ok().and_then(|json| predicate.validate(json).ok()).unwrap_or(false),
+            (HttpStubRequest::XmlRequest { body: etalon, .. }, Ok(body)) =>
+                canonicalize(body).is_ok_and(|actual| actual == etalon.canonical),
+            (HttpStubRequest::XPathRequest { body: predicate, namespaces, .. }, Ok(body)) =>
+                parser::parse(body).ok().and_then(|xml| predicate.validate(&xml.as_document(), namespaces).ok()).unwrap_or(false),
+            (HttpStubRequest::FormRequest { body: predicate, .. }, Ok(body)) =>
+                predicate.validate(urlencoded_to_json(body)).unwrap_or(false)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(tag = "mode")]
 pub enum HttpStubResponse {
     #[serde(rename = "raw")]
@@ -53,7 +227,7 @@ pub enum HttpStubResponse {
         headers: HashMap<String, String>,
         body: String,
         #[serde(skip_serializing_if = "Option::is_none")]
-        delay: Option<Duration>
+        delay: Option<Delay>
     },
     #[serde(rename = "json")]
     JsonResponse {
@@ -61,15 +235,93 @@ pub enum HttpStubResponse {
         headers: HashMap<String, String>,
         body: Value,
         #[serde(skip_serializing_if = "Option::is_none")]
-        delay: Option<Duration>,
+        delay: Option<Delay>,
         is_template: bool
+    },
+    /// Placeholders of templated XML may refer to the context or be XPath expressions
+    /// over XML request body, like `${/soap:Envelope/soap:Body/user/@id}`
+    #[serde(rename = "xml")]
+    XmlResponse {
+        code: u16,
+        headers: HashMap<String, String>,
+        #[serde(deserialize_with = "deserialize_xml")]
+        body: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        delay: Option<Delay>,
+        is_template: bool,
+        /// Prefixes available in the XPath expressions
+        #[serde(default)]
+        #[serde(skip_serializing_if = "HashMap::is_empty")]
+        namespaces: HashMap<String, String>
+    },
+    #[serde(rename = "binary")]
+    BinaryResponse {
+        code: u16,
+        headers: HashMap<String, String>,
+        /// Base64-encoded in the stub definition
+        #[serde(with = "crate::utils::serde_base64")]
+        body: Vec<u8>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        delay: Option<Delay>
+    },
+    /// Returns the content of an uploaded blob, its content type is used unless set in `headers`
+    #[serde(rename = "file")]
+    FileResponse {
+        code: u16,
+        headers: HashMap<String, String>,
+        blob_id: i32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        delay: Option<Delay>
+    },
+    /// Forwards the request to `uri` and returns upstream response as is
+    #[serde(rename = "proxy")]
+    ProxyResponse {
+        uri: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        delay: Option<Delay>
+    },
+    /// Forwards the request to `uri` and patches fields of upstream JSON response
+    #[serde(rename = "json-proxy")]
+    JsonProxyResponse {
+        uri: String,
+        patch: HashMap<JsonOptic, String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        delay: Option<Delay>
+    }
+}
+
+impl HttpStubResponse {
+    pub fn delay(&self) -> Option<&Delay> {
+        match self {
+            HttpStubResponse::RawResponse { delay, .. } => delay.as_ref(),
+            HttpStubResponse::JsonResponse { delay, .. } => delay.as_ref(),
+            HttpStubResponse::XmlResponse { delay, .. } => delay.as_ref(),
+            HttpStubResponse::BinaryResponse { delay, .. } => delay.as_ref(),
+            HttpStubResponse::FileResponse { delay, .. } => delay.as_ref(),
+            HttpStubResponse::ProxyResponse { delay, .. } => delay.as_ref(),
+            HttpStubResponse::JsonProxyResponse { delay, .. } => delay.as_ref()
+        }
+    }
+
+    /// Substitutes context values into the body of a templated response.
+    /// Request body is used by XPath placeholders of XML responses
+    pub fn render_template(&mut self, context: Value, request_body: &str) {
+        match self {
+            HttpStubResponse::JsonResponse { body, is_template: true, .. } => body.substitute_in_place(context),
+            HttpStubResponse::XmlResponse { body, is_template: true, namespaces, .. } => {
+                let source = parser::parse(request_body).ok();
+                *body = render_xml(body, &context, source.as_ref().map(|p| p.as_document()).as_ref(), namespaces);
+            }
+            _ => ()
+        }
     }
 }
 
 #[apply(NewInsertable!)]
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, AsChangeset, Serialize)]
 #[diesel(table_name = crate::schema::stub)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(treat_none_as_null = true)]
 pub struct HttpStub {
     pub id: i32,
     pub created: DateTime<Utc>,
@@ -81,19 +333,48 @@ pub struct HttpStub {
     pub path: Option<String>,
     pub path_pattern: Option<String>,
     pub seed: Option<Value>,
-    pub state: Option<Json<HashMap<JsonOptic, HashMap<SqlKeyword, Value>>>>,
+    pub state: Option<Json<StateSpec>>,
     pub request: Json<HttpStubRequest>,
     pub persist: Option<Json<HashMap<JsonOptic, Value>>>,
     pub response: Json<HttpStubResponse>,
     pub callback: Option<Json<Callback>>
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Compiles stub path pattern, which should match the whole request path
+pub fn path_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("^(?:{})$", pattern))
+}
+
+#[cfg(test)]
+impl HttpStub {
+    /// Persistent stub of `alpha` service, matching any request on the route and responding with 204
+    pub fn at_route(id: i32, method: HttpMethod, path: Option<&str>, path_pattern: Option<&str>) -> HttpStub {
+        HttpStub {
+            id,
+            created: Utc::now(),
+            scope: Scope::Persistent,
+            times: None,
+            service_suffix: "alpha".to_string(),
+            name: "test".to_string(),
+            method,
+            path: path.map(str::to_string),
+            path_pattern: path_pattern.map(str::to_string),
+            seed: None,
+            state: None,
+            request: Json::new(serde_json::from_value(serde_json::json!({"mode": "no_body", "headers": {}})).unwrap()),
+            persist: None,
+            response: Json::new(serde_json::from_value(serde_json::json!({"mode": "raw", "code": 204, "headers": {}, "body": ""})).unwrap()),
+            callback: None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum CallbackResponseMode {
     Json
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(tag = "mode")]
 pub enum CallbackRequest {
     #[serde(rename = "no_body")]
@@ -118,7 +399,7 @@ pub enum CallbackRequest {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Callback {
     HttpCallback {
         request: CallbackRequest,
@@ -133,7 +414,7 @@ pub enum Callback {
         callback: Option<Box<Callback>>,
         #[serde(skip_serializing_if = "Option::is_none")]
         #[serde(default)]
-        delay: Option<Duration>
+        delay: Option<Delay>
     }
 }
 
@@ -145,4 +426,246 @@ pub struct State {
     pub id: i32,
     pub created: DateTime<Utc>,
     pub data: Value
+}
+
+#[apply(NewInsertable!)]
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::blob)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Blob {
+    pub id: i32,
+    pub created: DateTime<Utc>,
+    pub name: String,
+    pub content_type: String,
+    pub content: Vec<u8>
+}
+
+/// Blob without its content
+#[derive(Queryable, Serialize)]
+pub struct BlobInfo {
+    pub id: i32,
+    pub created: DateTime<Utc>,
+    pub name: String,
+    pub content_type: String,
+    pub size: i32
+}
+
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name = crate::schema::service)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Service {
+    pub suffix: String,
+    pub name: String
+}
+
+#[cfg(test)]
+mod persistent_tests {
+    use crate::model::persistent::{HttpStubRequest, HttpStubResponse};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn headers_should_be_matched_case_insensitively() {
+        let request = serde_json::from_value::<HttpStubRequest>(json!({
+            "mode": "no_body",
+            "headers": {"Content-Type": "application/json"}
+        })).unwrap();
+
+        assert!(request.check_headers(&HashMap::from([
+            ("content-type".to_string(), "application/json".to_string()),
+            ("x-trace-id".to_string(), "42".to_string())
+        ])));
+        assert!(!request.check_headers(&HashMap::from([("content-type".to_string(), "text/plain".to_string())])));
+        assert!(!request.check_headers(&HashMap::new()));
+    }
+
+    #[test]
+    fn query_should_be_checked_with_predicate() {
+        let request = serde_json::from_value::<HttpStubRequest>(json!({
+            "mode": "no_body",
+            "headers": {},
+            "query": {"id": {"==": "42"}, "tags": {"&[_]": ["a", "b"]}}
+        })).unwrap();
+
+        assert!(request.check_query(&json!({"id": "42", "tags": ["b", "a", "c"]})));
+        assert!(!request.check_query(&json!({"id": "42", "tags": "a"})));
+        assert!(!request.check_query(&json!({"tags": ["a", "b"]})));
+    }
+
+    #[test]
+    fn json_body_should_be_equal() {
+        let request = serde_json::from_value::<HttpStubRequest>(json!({
+            "mode": "json",
+            "headers": {},
+            "body": {"a": 1, "b": [true, null]}
+        })).unwrap();
+
+        assert!(request.check_body(r#"{"b": [true, null], "a": 1}"#.as_bytes(), None));
+        assert!(!request.check_body(r#"{"a": 1}"#.as_bytes(), None));
+        assert!(!request.check_body("not a json".as_bytes(), None));
+    }
+
+    #[test]
+    fn raw_body_should_be_equal() {
+        let request = serde_json::from_value::<HttpStubRequest>(json!({
+            "mode": "raw",
+            "headers": {},
+            "body": "some text"
+        })).unwrap();
+
+        assert!(request.check_body("some text".as_bytes(), None));
+        assert!(!request.check_body("some text ".as_bytes(), None));
+    }
+
+    #[test]
+    fn jlens_body_should_satisfy_predicate() {
+        let request = serde_json::from_value::<HttpStubRequest>(json!({
+            "mode": "jlens",
+            "headers": {},
+            "body": {"user.age": {">=": 18}}
+        })).unwrap();
+
+        assert!(request.check_body(r#"{"user": {"age": 18}}"#.as_bytes(), None));
+        assert!(!request.check_body(r#"{"user": {"age": 17}}"#.as_bytes(), None));
+        assert!(!request.check_body("[1, 2".as_bytes(), None));
+    }
+
+    #[test]
+    fn xml_body_should_be_compared_canonically() {
+        let request = serde_json::from_value::<HttpStubRequest>(json!({
+            "mode": "xml",
+            "headers": {},
+            "body": "<user id=\"1\"><name>peka</name></user>"
+        })).unwrap();
+
+        assert!(request.check_body("<user id='1'>\n  <name>peka</name>\n</user>".as_bytes(), None));
+        assert!(!request.check_body("<user id='2'><name>peka</name></user>".as_bytes(), None));
+        assert!(!request.check_body("<user".as_bytes(), None));
+
+        assert!(serde_json::from_value::<HttpStubRequest>(json!({"mode": "xml", "headers": {}, "body": "<user>"})).is_err());
+
+        // the etalon is stored as it was defined
+        assert_eq!(serde_json::to_value(&request).unwrap()["body"], json!("<user id=\"1\"><name>peka</name></user>"));
+    }
+
+    #[test]
+    fn xpath_body_should_satisfy_predicate() {
+        let request = serde_json::from_value::<HttpStubRequest>(json!({
+            "mode": "xpath",
+            "headers": {},
+            "body": {"/soap:Envelope/soap:Body/user/age": {">=": 18}},
+            "namespaces": {"soap": "urn:soap"}
+        })).unwrap();
+
+        assert!(request.check_body(r#"<e:Envelope xmlns:e="urn:soap"><e:Body><user><age>18</age></user></e:Body></e:Envelope>"#.as_bytes(), None));
+        assert!(!request.check_body(r#"<e:Envelope xmlns:e="urn:soap"><e:Body><user><age>17</age></user></e:Body></e:Envelope>"#.as_bytes(), None));
+        assert!(!request.check_body(r#"{"user": {"age": 18}}"#.as_bytes(), None));
+    }
+
+    #[test]
+    fn multipart_parts_should_be_matched_by_name_and_mode() {
+        let request = serde_json::from_value::<HttpStubRequest>(json!({
+            "mode": "multipart",
+            "headers": {},
+            "body": [
+                {"name": "meta", "mode": "jlens", "value": {"id": {">": 0}}},
+                {"name": "doc", "mode": "xml", "value": "<doc><title>peka</title></doc>"},
+                {"name": "file", "headers": {"Content-Type": "application/octet-stream"}, "mode": "any"}
+            ]
+        })).unwrap();
+
+        let content_type = Some("multipart/form-data; boundary=b0");
+        let body = |meta: &str| [
+            b"--b0\r\nContent-Disposition: form-data; name=\"meta\"\r\n\r\n".as_slice(), meta.as_bytes(),
+            b"\r\n--b0\r\nContent-Disposition: form-data; name=\"doc\"\r\n\r\n<doc>\n <title>peka</title>\n</doc>",
+            b"\r\n--b0\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.bin\"\r\nContent-Type: application/octet-stream\r\n\r\n\x00\xff",
+            b"\r\n--b0\r\nContent-Disposition: form-data; name=\"extra\"\r\n\r\nignored\r\n--b0--\r\n"
+        ].concat();
+
+        assert!(request.check_body(&body(r#"{"id": 1}"#), content_type));
+        assert!(!request.check_body(&body(r#"{"id": 0}"#), content_type));
+        assert!(!request.check_body(&body(r#"{"id": 1}"#), None));
+        assert!(!request.check_body(b"--b0\r\nContent-Disposition: form-data; name=\"meta\"\r\n\r\n{\"id\": 1}\r\n--b0--", content_type));
+    }
+
+    #[test]
+    fn form_fields_should_satisfy_predicate() {
+        let request = serde_json::from_value::<HttpStubRequest>(json!({
+            "mode": "form",
+            "headers": {},
+            "body": {"user": {"==": "peka"}, "tags": {"&[_]": ["a", "b"]}}
+        })).unwrap();
+
+        assert!(request.check_body(b"user=peka&tags=b&tags=a&extra=1", None));
+        assert!(!request.check_body(b"user=name&tags=b&tags=a", None));
+        assert!(!request.check_body(b"user=peka", None));
+    }
+
+    #[test]
+    fn templated_json_response_should_be_rendered() {
+        let mut response = serde_json::from_value::<HttpStubResponse>(json!({
+            "mode": "json",
+            "code": 200,
+            "headers": {},
+            "body": {"id": "${pathParts.id}", "name": "${req.name}", "visits": "${state.visits}", "token": "${seed.token}"},
+            "is_template": true
+        })).unwrap();
+
+        response.render_template(json!({
+            "req": {"name": "peka"},
+            "pathParts": {"id": "42"},
+            "state": {"visits": 3},
+            "seed": {"token": "abc"}
+        }), "");
+
+        match response {
+            HttpStubResponse::JsonResponse { body, .. } =>
+                assert_eq!(body, json!({"id": "42", "name": "peka", "visits": 3, "token": "abc"})),
+            _ => panic!("Unexpected response mode")
+        }
+    }
+
+    #[test]
+    fn templated_xml_response_should_echo_request_fields() {
+        let mut response = serde_json::from_value::<HttpStubResponse>(json!({
+            "mode": "xml",
+            "code": 200,
+            "headers": {},
+            "body": "<reply><id>${/soap:Envelope/soap:Body/user/@id}</id><visits>${state.visits}</visits></reply>",
+            "is_template": true,
+            "namespaces": {"soap": "urn:soap"}
+        })).unwrap();
+
+        response.render_template(
+            json!({"state": {"visits": 3}}),
+            r#"<e:Envelope xmlns:e="urn:soap"><e:Body><user id="u1"/></e:Body></e:Envelope>"#
+        );
+
+        match response {
+            HttpStubResponse::XmlResponse { body, .. } => assert_eq!(body, "<reply><id>u1</id><visits>3</visits></reply>"),
+            _ => panic!("Unexpected response mode")
+        }
+
+        assert!(serde_json::from_value::<HttpStubResponse>(json!({
+            "mode": "xml", "code": 200, "headers": {}, "body": "<reply>", "is_template": false
+        })).is_err());
+    }
+
+    #[test]
+    fn non_templated_json_response_should_be_kept_intact() {
+        let mut response = serde_json::from_value::<HttpStubResponse>(json!({
+            "mode": "json",
+            "code": 200,
+            "headers": {},
+            "body": {"name": "${req.name}"},
+            "is_template": false
+        })).unwrap();
+
+        response.render_template(json!({"req": {"name": "peka"}}), "");
+
+        match response {
+            HttpStubResponse::JsonResponse { body, .. } => assert_eq!(body, json!({"name": "${req.name}"})),
+            _ => panic!("Unexpected response mode")
+        }
+    }
 }
\ No newline at end of file
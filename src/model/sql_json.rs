@@ -1,4 +1,10 @@
-use serde::{Deserialize, Serialize};
+use crate::predicate_dsl::group::{nested_specs, Group};
+use crate::predicate_dsl::json::ConditionProblem;
+use crate::utils::js::optic::JsonOptic;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 
 #[derive(Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
 pub enum Keyword {
@@ -18,4 +24,74 @@ pub enum Keyword {
     Rx,
     #[serde(rename = "^")]
     StartsWith
-}
\ No newline at end of file
+}
+
+/// State query: conditions on optics along with `$or`, `$and` and `$not` groups of nested queries
+#[derive(Debug, Default, PartialEq)]
+pub struct StateSpec {
+    pub conditions: HashMap<JsonOptic, HashMap<Keyword, Value>>,
+    pub groups: Vec<Group<StateSpec>>
+}
+
+impl StateSpec {
+    /// Lists faulty conditions of the query: unknown keywords, malformed groups and arguments unsuitable for jsonpath
+    pub fn problems(spec: &Value) -> Vec<ConditionProblem> {
+        spec_problems(spec, "")
+    }
+}
+
+fn spec_problems(spec: &Value, location: &str) -> Vec<ConditionProblem> {
+    let problem = |field: String, keyword: Option<String>, value: &Value, reason: &str| ConditionProblem {
+        field, keyword, value: value.clone(), reason: reason.to_string()
+    };
+
+    let spec = match spec {
+        Value::Object(spec) => spec,
+        other => return vec![problem(location.trim_end_matches('.').to_string(), None, other, "Query should be an object")]
+    };
+
+    let mut problems = vec![];
+
+    for (field, conds) in spec.iter() {
+        let field_location = format!("{}{}", location, field);
+
+        match (nested_specs(field, conds), conds) {
+            (Some(Ok(nested)), _) => nested.into_iter()
+                .for_each(|(nested_location, nested_spec)| problems.extend(spec_problems(nested_spec, &format!("{}{}.", location, nested_location)))),
+            (Some(Err(reason)), _) => problems.push(problem(field_location, None, conds, &reason)),
+            (None, Value::Object(conds)) => {
+                for (kwd, etalon) in conds.iter() {
+                    let reason = match (serde_json::from_value::<Keyword>(Value::String(kwd.clone())), etalon) {
+                        (Err(_), _) => "Unknown keyword",
+                        (Ok(Keyword::Rx | Keyword::StartsWith), Value::String(_)) => continue,
+                        (Ok(Keyword::Rx | Keyword::StartsWith), _) => "Argument should be a string",
+                        (Ok(_), _) => continue
+                    };
+
+                    problems.push(problem(field_location.clone(), Some(kwd.clone()), etalon, reason));
+                }
+            }
+            (None, other) => problems.push(problem(field_location, None, other, "Conditions should be an object"))
+        }
+    }
+
+    problems
+}
+
+impl Serialize for StateSpec {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let conditions = serde_json::to_value(&self.conditions).map_err(serde::ser::Error::custom)?;
+
+        Group::merge(conditions, &self.groups).map_err(serde::ser::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl <'de> Deserialize<'de> for StateSpec {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let mut spec = Map::<String, Value>::deserialize(deserializer)?;
+        let groups = Group::extract(&mut spec).map_err(D::Error::custom)?;
+        let conditions = serde_json::from_value(Value::Object(spec)).map_err(D::Error::custom)?;
+
+        Ok(StateSpec { conditions, groups })
+    }
+}
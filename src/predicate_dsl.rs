@@ -1,2 +1,5 @@
+pub mod condition;
+pub mod group;
 pub mod json;
-pub mod keyword;
\ No newline at end of file
+pub mod keyword;
+pub mod xml;
\ No newline at end of file
@@ -0,0 +1,219 @@
+use crate::predicate_dsl::json::{validate_condition, ValidationError};
+use crate::predicate_dsl::keyword::Keyword;
+use crate::utils::{IntoBD, IntoUSize};
+use bigdecimal::BigDecimal;
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Condition with the argument prepared once for all checks: regexes are compiled,
+/// numeric bounds are converted into BigDecimal, `[_]` and `![_]` lists are hashed
+pub(super) struct CompiledCondition {
+    pub keyword: Keyword,
+    pub argument: Value,
+    prepared: Prepared
+}
+
+enum Prepared {
+    Plain,
+    Regex(Regex),
+    Bound(BigDecimal),
+    Set(ValueSet),
+    Nested(Vec<CompiledCondition>)
+}
+
+impl CompiledCondition {
+    /// Returns None if the argument doesn't suit the keyword
+    pub fn compile(keyword: &Keyword, argument: &Value) -> Option<CompiledCondition> {
+        let prepared = match (keyword, argument) {
+            (Keyword::Rx, Value::String(rx)) => Prepared::Regex(Regex::new(rx).ok()?),
+            // nested conditions are checked by their own compilation
+            (Keyword::Any | Keyword::All, Value::Object(nested)) if !nested.is_empty() => Prepared::Nested(compile_nested(nested)?),
+            _ if !validate_condition(keyword, argument) => return None,
+            (Keyword::Greater | Keyword::Gte | Keyword::Less | Keyword::Lte, Value::Number(bound)) => Prepared::Bound(bound.to_big_decimal()),
+            (Keyword::In | Keyword::NotIn, Value::Array(values)) => Prepared::Set(ValueSet::new(values)),
+            _ => Prepared::Plain
+        };
+
+        Some(CompiledCondition { keyword: keyword.clone(), argument: argument.clone(), prepared })
+    }
+
+    pub fn validate(&self, value: &Value) -> Result<bool, ValidationError<'_>> {
+        match (&self.prepared, value) {
+            (Prepared::Regex(rx), Value::String(s)) => Ok(rx.is_match(s)),
+            (Prepared::Regex(_), _) => Err(ValidationError::DataError),
+            (Prepared::Bound(bound), Value::Number(nv)) => {
+                let nv = nv.to_big_decimal();
+
+                match self.keyword {
+                    Keyword::Greater => Ok(nv > *bound),
+                    Keyword::Gte => Ok(nv >= *bound),
+                    Keyword::Less => Ok(nv < *bound),
+                    _ => Ok(nv <= *bound)
+                }
+            }
+            (Prepared::Bound(_), _) => Err(ValidationError::DataError),
+            (Prepared::Set(set), value) => {
+                let found = match value {
+                    Value::Array(vals) => vals.iter().any(|v| set.contains(v)),
+                    val => set.contains(val)
+                };
+
+                Ok(if self.keyword == Keyword::In { found } else { !found })
+            }
+            (Prepared::Nested(_), _) => Err(self.condition_error()),
+            (Prepared::Plain, value) => self.validate_plain(value)
+        }
+    }
+
+    /// Checks nested conditions of `any` / `all` against every value
+    pub fn validate_quantified(&self, values: &[&Value]) -> Result<bool, ValidationError<'_>> {
+        let conds = match &self.prepared {
+            Prepared::Nested(conds) => conds,
+            _ => return Err(self.condition_error())
+        };
+
+        let mut matches = vec![];
+
+        for value in values {
+            let checks = conds.iter().map(|cond| cond.validate(value)).collect::<Vec<_>>();
+
+            if checks.iter().any(|check| matches!(check, Err(ValidationError::ConditionError { .. }))) {
+                return Err(self.condition_error());
+            }
+
+            matches.push(checks.iter().all(|check| matches!(check, Ok(true))));
+        }
+
+        match self.keyword {
+            Keyword::Any => Ok(matches.into_iter().any(|m| m)),
+            _ => Ok(matches.into_iter().all(|m| m))
+        }
+    }
+
+    fn validate_plain(&self, value: &Value) -> Result<bool, ValidationError<'_>> {
+        match (&self.keyword, &self.argument, value) {
+            (Keyword::Equals, v_eq, val) => Ok(v_eq == val),
+            (Keyword::NotEq, v_neq, val) => Ok(v_neq != val),
+            (Keyword::Size, Value::Number(size), Value::String(s)) => Ok(s.len() == size.to_usize()),
+            (Keyword::Size, Value::Number(size), Value::Array(v)) => Ok(v.len() == size.to_usize()),
+            (Keyword::Size, Value::Number(_), _) => Err(ValidationError::DataError),
+            (Keyword::Exists, Value::Bool(true), val) => Ok(!val.is_null()),
+            (Keyword::Exists, Value::Bool(false), val) => Ok(val.is_null()),
+            (Keyword::AllIn, Value::Array(mandatory), Value::Array(vals)) => Ok(mandatory.iter().all(|mv| vals.contains(mv))),
+            (Keyword::AllIn, Value::Array(_), _) => Err(ValidationError::DataError),
+            (Keyword::StartsWith, Value::String(prefix), Value::String(s)) => Ok(s.starts_with(prefix.as_str())),
+            (Keyword::StartsWith, Value::String(_), _) => Err(ValidationError::DataError),
+            (Keyword::EqualsIgnoreCase, Value::String(v_eq), Value::String(s)) => Ok(s.to_lowercase() == v_eq.to_lowercase()),
+            (Keyword::EqualsIgnoreCase, Value::String(_), _) => Err(ValidationError::DataError),
+            (Keyword::Contains, Value::String(part), Value::String(s)) => Ok(s.contains(part.as_str())),
+            (Keyword::Contains, Value::String(_), _) => Err(ValidationError::DataError),
+            (Keyword::SizeGreater, Value::Number(size), Value::String(s)) => Ok(s.len() > size.to_usize()),
+            (Keyword::SizeGreater, Value::Number(size), Value::Array(v)) => Ok(v.len() > size.to_usize()),
+            (Keyword::SizeGreater, Value::Number(_), _) => Err(ValidationError::DataError),
+            (Keyword::SizeLess, Value::Number(size), Value::String(s)) => Ok(s.len() < size.to_usize()),
+            (Keyword::SizeLess, Value::Number(size), Value::Array(v)) => Ok(v.len() < size.to_usize()),
+            (Keyword::SizeLess, Value::Number(_), _) => Err(ValidationError::DataError),
+            (Keyword::KeyIn, Value::Array(keys), Value::Object(obj)) => Ok(keys.iter().any(|k| has_key(obj, k))),
+            (Keyword::KeyNotIn, Value::Array(keys), Value::Object(obj)) => Ok(!keys.iter().any(|k| has_key(obj, k))),
+            (Keyword::AllKeysIn, Value::Array(keys), Value::Object(obj)) => Ok(keys.iter().all(|k| has_key(obj, k))),
+            (Keyword::KeyIn | Keyword::KeyNotIn | Keyword::AllKeysIn, Value::Array(_), _) => Err(ValidationError::DataError),
+            _ => Err(self.condition_error())
+        }
+    }
+
+    fn condition_error(&self) -> ValidationError<'_> {
+        ValidationError::ConditionError { keyword: &self.keyword, argument: &self.argument }
+    }
+}
+
+fn compile_nested(nested: &Map<String, Value>) -> Option<Vec<CompiledCondition>> {
+    nested.iter()
+        .map(|(kwd, argument)| match serde_json::from_value::<Keyword>(Value::String(kwd.clone())).ok()? {
+            Keyword::Any | Keyword::All => None,
+            keyword => CompiledCondition::compile(&keyword, argument)
+        })
+        .collect()
+}
+
+fn has_key(obj: &Map<String, Value>, key: &Value) -> bool {
+    key.as_str().is_some_and(|k| obj.contains_key(k))
+}
+
+/// Set of JSON values, hashed consistently with their equality
+struct ValueSet(HashMap<u64, Vec<Value>>);
+
+impl ValueSet {
+    fn new(values: &[Value]) -> ValueSet {
+        let mut buckets: HashMap<u64, Vec<Value>> = HashMap::new();
+
+        for value in values {
+            buckets.entry(hash_of(value)).or_default().push(value.clone());
+        }
+
+        ValueSet(buckets)
+    }
+
+    fn contains(&self, value: &Value) -> bool {
+        self.0.get(&hash_of(value)).is_some_and(|bucket| bucket.contains(value))
+    }
+}
+
+fn hash_of(value: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash_into(value, &mut hasher);
+    hasher.finish()
+}
+
+fn hash_into<H: Hasher>(value: &Value, state: &mut H) {
+    match value {
+        Value::Null => 0u8.hash(state),
+        Value::Bool(b) => (1u8, b).hash(state),
+        Value::Number(n) => (2u8, n.to_string()).hash(state),
+        Value::String(s) => (3u8, s).hash(state),
+        Value::Array(vs) => {
+            (4u8, vs.len()).hash(state);
+            vs.iter().for_each(|v| hash_into(v, state));
+        }
+        // equal objects may have different order of keys
+        Value::Object(obj) => {
+            (5u8, obj.len()).hash(state);
+            let mut keys = obj.keys().collect::<Vec<_>>();
+            keys.sort();
+            for key in keys {
+                key.hash(state);
+                hash_into(&obj[key], state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod condition_tests {
+    use crate::predicate_dsl::condition::CompiledCondition;
+    use crate::predicate_dsl::keyword::Keyword;
+    use serde_json::json;
+
+    #[test]
+    fn faulty_conditions_should_not_compile() {
+        assert!(CompiledCondition::compile(&Keyword::Rx, &json!("(")).is_none());
+        assert!(CompiledCondition::compile(&Keyword::Greater, &json!("1")).is_none());
+        assert!(CompiledCondition::compile(&Keyword::Any, &json!({"~=": "("})).is_none());
+        assert!(CompiledCondition::compile(&Keyword::Any, &json!({"all": {"==": 1}})).is_none());
+        assert!(CompiledCondition::compile(&Keyword::All, &json!({})).is_none());
+        assert!(CompiledCondition::compile(&Keyword::All, &json!({"~=": "^a", ">": 1})).is_some());
+    }
+
+    #[test]
+    fn hashed_lists_should_match_composite_values() {
+        let cond = CompiledCondition::compile(&Keyword::In, &json!([{"a": 1, "b": [true, null]}, 1.5, "x"])).unwrap();
+
+        assert!(cond.validate(&json!({"b": [true, null], "a": 1})).ok().unwrap());
+        assert!(cond.validate(&json!(1.5)).ok().unwrap());
+        assert!(cond.validate(&json!(["y", "x"])).ok().unwrap());
+        assert!(!cond.validate(&json!({"a": 1})).ok().unwrap());
+        assert!(!cond.validate(&json!("1.5")).ok().unwrap());
+    }
+}
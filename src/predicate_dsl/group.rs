@@ -0,0 +1,76 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+const OR: &str = "$or";
+const AND: &str = "$and";
+const NOT: &str = "$not";
+
+/// Nested predicates stored next to the conditions under `$or`, `$and` (non-empty arrays)
+/// and `$not` keys. Groups and conditions of a predicate should all hold
+#[derive(Debug, PartialEq)]
+pub enum Group<P> {
+    Or(Vec<P>),
+    And(Vec<P>),
+    Not(Box<P>)
+}
+
+impl <P: DeserializeOwned> Group<P> {
+    /// Removes group entries from the specification, leaving the conditions only
+    pub fn extract(spec: &mut Map<String, Value>) -> Result<Vec<Group<P>>, String> {
+        let mut groups = vec![];
+
+        if let Some(preds) = spec.remove(OR) {
+            groups.push(Group::Or(parse_list(OR, preds)?));
+        }
+        if let Some(preds) = spec.remove(AND) {
+            groups.push(Group::And(parse_list(AND, preds)?));
+        }
+        if let Some(pred) = spec.remove(NOT) {
+            groups.push(Group::Not(Box::new(serde_json::from_value(pred).map_err(|e| e.to_string())?)));
+        }
+
+        Ok(groups)
+    }
+}
+
+impl <P: Serialize> Group<P> {
+    /// Puts groups back into the serialized conditions
+    pub fn merge(conditions: Value, groups: &[Group<P>]) -> Result<Value, serde_json::Error> {
+        let mut spec = match conditions {
+            Value::Object(spec) => spec,
+            _ => Map::new()
+        };
+
+        for group in groups {
+            match group {
+                Group::Or(preds) => spec.insert(OR.to_string(), serde_json::to_value(preds)?),
+                Group::And(preds) => spec.insert(AND.to_string(), serde_json::to_value(preds)?),
+                Group::Not(pred) => spec.insert(NOT.to_string(), serde_json::to_value(pred)?)
+            };
+        }
+
+        Ok(Value::Object(spec))
+    }
+}
+
+/// Nested specifications of a group entry along with their locations like `$or[1]`.
+/// Returns None if the entry is not a group
+pub fn nested_specs<'v>(key: &str, value: &'v Value) -> Option<Result<Vec<(String, &'v Value)>, String>> {
+    match (key, value) {
+        (OR | AND, Value::Array(specs)) if !specs.is_empty() =>
+            Some(Ok(specs.iter().enumerate().map(|(idx, spec)| (format!("{}[{}]", key, idx), spec)).collect())),
+        (OR | AND, _) => Some(Err(format!("{} should be a non-empty array of predicates", key))),
+        (NOT, spec) => Some(Ok(vec![(key.to_string(), spec)])),
+        _ => None
+    }
+}
+
+fn parse_list<P: DeserializeOwned>(key: &str, preds: Value) -> Result<Vec<P>, String> {
+    match preds {
+        Value::Array(preds) if !preds.is_empty() => preds.into_iter()
+            .map(|pred| serde_json::from_value(pred).map_err(|e| e.to_string()))
+            .collect(),
+        _ => Err(format!("{} should be a non-empty array of predicates", key))
+    }
+}
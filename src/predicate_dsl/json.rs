@@ -1,106 +1,125 @@
+use crate::predicate_dsl::condition::CompiledCondition;
+use crate::predicate_dsl::group::{nested_specs, Group};
 use crate::predicate_dsl::keyword::Keyword;
-use crate::utils::{IntoBD, IntoUSize};
 use crate::utils::js::optic::{JsonOptic, ValueExt};
 use regex::Regex;
 use serde::{Serialize, Deserialize, Serializer, Deserializer};
 use serde::de::Error;
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 
 type Spec = HashMap<JsonOptic, HashMap<Keyword, Value>>;
 type Condition<'r> = (&'r Keyword, &'r Value);
 
+/// Conditions on values found by optics. Optics with `$` may find several values,
+/// plain conditions check the first one, while conditions nested into `any` / `all`
+/// should hold for some / every found value (`all` holds if nothing is found).
+/// Nested predicates may be combined with `$or`, `$and` and `$not`
+#[derive(Default)]
 pub struct JsonPredicate {
-    definition: Spec
+    definition: Spec,
+    compiled: Vec<(JsonOptic, Vec<CompiledCondition>)>,
+    groups: Vec<Group<JsonPredicate>>
 }
 
 impl JsonPredicate {
     pub fn validate(&self, json: Value) -> Result<bool, PredicateConstructionError<'_>> {
+        self.evaluate(&json)
+    }
+
+    fn evaluate(&self, json: &Value) -> Result<bool, PredicateConstructionError<'_>> {
         let mut result: Vec<Result<bool, ValidationError<'_>>> = vec![];
 
-        for (jo, conds) in self.definition.iter() {
+        for (jo, conds) in self.compiled.iter() {
             let all_data = json.get_all(jo);
             let data = all_data.first().unwrap_or(&&Value::Null);
 
-            for (kwd, etalon) in conds.iter() {
-                result.push(JsonPredicate::validate_one(kwd, etalon, *data));
+            for cond in conds.iter() {
+                result.push(match cond.keyword {
+                    Keyword::Any | Keyword::All => cond.validate_quantified(&all_data),
+                    _ => cond.validate(data)
+                });
             }
         }
 
-        let (oks, errs): (Vec<_>, Vec<_>) = result.into_iter().partition(|el| el.is_ok());
-
-        if errs.len() == 0 {
-            Ok(oks.into_iter().filter_map(|el| el.ok()).all(|el| el))
-        } else if errs.iter().all(|err| err.as_ref().err().unwrap().is_data_error()) {
-            Ok(false)
-        } else {
-            let condition_errors = errs.into_iter().filter_map(|el| el.err())
-                .filter_map(|err| match err {
-                    ValidationError::ConditionError { keyword, argument } => Some((keyword, argument)),
-                    _ => None
-                }).collect::<Vec<_>>();
-            Err(PredicateConstructionError { problems: condition_errors })
+        for group in self.groups.iter() {
+            match evaluate_group(group, json) {
+                Ok(res) => result.push(Ok(res)),
+                Err(e) => result.extend(e.problems.into_iter().map(|(keyword, argument)| Err(ValidationError::ConditionError { keyword, argument })))
+            }
         }
+
+        combine_results(result)
+    }
+
+    /// Lists faulty conditions of the specification, it can be deserialized only if there are none
+    pub fn problems(spec: &Value) -> Vec<ConditionProblem> {
+        spec_problems(spec, "")
     }
+}
+
+fn evaluate_group<'r>(group: &'r Group<JsonPredicate>, json: &Value) -> Result<bool, PredicateConstructionError<'r>> {
+    let preds = match group {
+        Group::Or(preds) | Group::And(preds) => preds.iter().collect::<Vec<_>>(),
+        Group::Not(pred) => vec![pred.as_ref()]
+    };
+
+    let mut results = vec![];
+    let mut problems = vec![];
 
-    fn validate_one<'r>(kwd: &'r Keyword, etalon: &'r Value, value: &Value) -> Result<bool, ValidationError<'r>> {
-        match (kwd, etalon, value) {
-            (Keyword::Equals, v_eq, val) => Ok(v_eq == val),
-            (Keyword::NotEq, v_neq, val) => Ok(v_neq != val),
-            (Keyword::Greater, Value::Number(l_b), Value::Number(nv)) => Ok(nv.to_big_decimal() > l_b.to_big_decimal()),
-            (Keyword::Greater, Value::Number(_), _) => Err(ValidationError::DataError),
-            (Keyword::Gte, Value::Number(l_b), Value::Number(nv)) => Ok(nv.to_big_decimal() >= l_b.to_big_decimal()),
-            (Keyword::Gte, Value::Number(_), _) => Err(ValidationError::DataError),
-            (Keyword::Less, Value::Number(u_b), Value::Number(nv)) => Ok(nv.to_big_decimal() < u_b.to_big_decimal()),
-            (Keyword::Less, Value::Number(_), _) => Err(ValidationError::DataError),
-            (Keyword::Lte, Value::Number(u_b), Value::Number(nv)) => Ok(nv.to_big_decimal() <= u_b.to_big_decimal()),
-            (Keyword::Lte, Value::Number(_), _) => Err(ValidationError::DataError),
-            (Keyword::Rx, Value::String(rx), Value::String(s)) => match Regex::new(rx) {
-                Ok(regex) => Ok(regex.is_match(s)),
-                _ => Err(ValidationError::ConditionError {keyword: kwd, argument: etalon } )
-            },
-            (Keyword::Rx, Value::String(rx), _) if Regex::new(rx).is_ok() => Err(ValidationError::DataError),
-            (Keyword::Size, Value::Number(size), Value::String(s)) => Ok(s.len() == size.to_usize()),
-            (Keyword::Size, Value::Number(size), Value::Array(v)) => Ok(v.len() == size.to_usize()),
-            (Keyword::Size, Value::Number(_), _) => Err(ValidationError::DataError),
-            (Keyword::Exists, Value::Bool(true), val) => Ok(!val.is_null()),
-            (Keyword::Exists, Value::Bool(false), val) => Ok(val.is_null()),
-            (Keyword::In, Value::Array(possible), Value::Array(vals)) => Ok(possible.iter().any(|pv| vals.contains(pv))),
-            (Keyword::In, Value::Array(possible), val) => Ok(possible.contains(val)),
-            (Keyword::NotIn, Value::Array(impossible), Value::Array(vals)) => Ok(impossible.iter().all(|iv| !vals.contains(iv))),
-            (Keyword::NotIn, Value::Array(impossible), val) => Ok(!impossible.contains(val)),
-            (Keyword::AllIn, Value::Array(mandatory), Value::Array(vals)) => Ok(mandatory.iter().all(|mv| vals.contains(mv))),
-            (Keyword::AllIn, Value::Array(_), _) => Err(ValidationError::DataError),
-            (k, v, _) => Err(ValidationError::ConditionError {keyword: k, argument: v } )
+    for pred in preds {
+        match pred.evaluate(json) {
+            Ok(res) => results.push(res),
+            Err(e) => problems.extend(e.problems)
         }
     }
+
+    if !problems.is_empty() {
+        return Err(PredicateConstructionError { problems });
+    }
+
+    match group {
+        Group::Or(_) => Ok(results.into_iter().any(|res| res)),
+        Group::And(_) => Ok(results.into_iter().all(|res| res)),
+        Group::Not(_) => Ok(!results[0])
+    }
 }
 
 impl Serialize for JsonPredicate {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
-        self.definition.serialize(serializer)
+        let conditions = serde_json::to_value(&self.definition).map_err(serde::ser::Error::custom)?;
+
+        Group::merge(conditions, &self.groups).map_err(serde::ser::Error::custom)?.serialize(serializer)
     }
 }
 
 impl <'de> Deserialize<'de> for JsonPredicate {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
-        let spec = Spec::deserialize(deserializer)?;
+        let mut raw_spec = Map::<String, Value>::deserialize(deserializer)?;
+        let groups = Group::extract(&mut raw_spec).map_err(D::Error::custom)?;
+        let spec = serde_json::from_value::<Spec>(Value::Object(raw_spec)).map_err(D::Error::custom)?;
 
         let mut faulty_fields: Vec<String> = vec![];
+        let mut compiled = vec![];
 
         for (optic, cond) in spec.iter() {
+            let mut conds = vec![];
+
             for (kwd, v) in cond.iter() {
-                if !validate_condition(kwd, v) {
-                    faulty_fields.push(optic.to_string());
+                match CompiledCondition::compile(kwd, v) {
+                    Some(compiled_cond) => conds.push(compiled_cond),
+                    None => faulty_fields.push(optic.to_string())
                 }
             }
+
+            compiled.push((optic.clone(), conds));
         }
 
         if !faulty_fields.is_empty() {
             Err(D::Error::custom(format!("Conditions are faulty on fields: {}", faulty_fields.join(", "))))
         } else {
-            Ok(JsonPredicate { definition: spec })
+            Ok(JsonPredicate { definition: spec, compiled, groups })
         }
     }
 }
@@ -110,28 +129,131 @@ impl Debug for JsonPredicate {
         write!(
             f,
             "{}",
-            serde_json::to_string(&self.definition).expect("Unserializable JsonPredicate!")
+            serde_json::to_string(self).expect("Unserializable JsonPredicate!")
         )
     }
 }
 
-fn validate_condition<'r>(kwd: &'r Keyword, etalon: &'r Value) -> bool {
-    match (kwd, etalon) {
-        (Keyword::Equals | Keyword::NotEq, _) => true,
-        (Keyword::Greater | Keyword::Gte | Keyword::Less | Keyword::Lte, Value::Number(_)) => true,
-        (Keyword::Rx, Value::String(rx)) if Regex::new(rx).is_ok() => true,
-        (Keyword::Size, Value::Number(_)) => true,
-        (Keyword::Exists, Value::Bool(_)) => true,
-        (Keyword::In | Keyword::NotIn | Keyword::AllIn, Value::Array(_)) => true,
-        (_, _) => false
+/// Data errors make the predicate fail, condition errors are reported
+pub(super) fn combine_results(result: Vec<Result<bool, ValidationError<'_>>>) -> Result<bool, PredicateConstructionError<'_>> {
+    let (oks, errs): (Vec<_>, Vec<_>) = result.into_iter().partition(|el| el.is_ok());
+
+    if errs.is_empty() {
+        Ok(oks.into_iter().filter_map(|el| el.ok()).all(|el| el))
+    } else if errs.iter().all(|err| err.as_ref().err().unwrap().is_data_error()) {
+        Ok(false)
+    } else {
+        let condition_errors = errs.into_iter().filter_map(|el| el.err())
+            .filter_map(|err| match err {
+                ValidationError::ConditionError { keyword, argument } => Some((keyword, argument)),
+                _ => None
+            }).collect::<Vec<_>>();
+        Err(PredicateConstructionError { problems: condition_errors })
+    }
+}
+
+pub(super) fn validate_condition<'r>(kwd: &'r Keyword, etalon: &'r Value) -> bool {
+    condition_problem(kwd, etalon).is_none()
+}
+
+/// Describes why the argument doesn't suit the keyword
+fn condition_problem(kwd: &Keyword, etalon: &Value) -> Option<String> {
+    let expected = match (kwd, etalon) {
+        (Keyword::Equals | Keyword::NotEq, _) => return None,
+        (Keyword::Rx, Value::String(rx)) => return Regex::new(rx).err().map(|e| format!("Invalid regex: {}", e)),
+        (Keyword::Greater | Keyword::Gte | Keyword::Less | Keyword::Lte, Value::Number(_)) => return None,
+        (Keyword::Size | Keyword::SizeGreater | Keyword::SizeLess, Value::Number(_)) => return None,
+        (Keyword::StartsWith | Keyword::EqualsIgnoreCase | Keyword::Contains, Value::String(_)) => return None,
+        (Keyword::Exists, Value::Bool(_)) => return None,
+        (Keyword::In | Keyword::NotIn | Keyword::AllIn, Value::Array(_)) => return None,
+        (Keyword::KeyIn | Keyword::KeyNotIn | Keyword::AllKeysIn, Value::Array(keys)) if keys.iter().all(Value::is_string) => return None,
+        (Keyword::Any | Keyword::All, Value::Object(nested)) if !nested.is_empty() => return nested.iter().find_map(|(k, v)| {
+            match serde_json::from_value::<Keyword>(Value::String(k.clone())) {
+                Ok(Keyword::Any | Keyword::All) => Some(format!("Quantifier '{}' can't be nested", k)),
+                Ok(nested_kwd) => condition_problem(&nested_kwd, v).map(|reason| format!("'{}': {}", k, reason)),
+                Err(_) => Some(format!("Unknown keyword '{}'", k))
+            }
+        }),
+        (Keyword::Greater | Keyword::Gte | Keyword::Less | Keyword::Lte, _) => "a number",
+        (Keyword::Size | Keyword::SizeGreater | Keyword::SizeLess, _) => "a number",
+        (Keyword::Rx | Keyword::StartsWith | Keyword::EqualsIgnoreCase | Keyword::Contains, _) => "a string",
+        (Keyword::Exists, _) => "a boolean",
+        (Keyword::In | Keyword::NotIn | Keyword::AllIn, _) => "an array",
+        (Keyword::KeyIn | Keyword::KeyNotIn | Keyword::AllKeysIn, _) => "an array of strings",
+        (Keyword::Any | Keyword::All, _) => "a non-empty object with conditions"
+    };
+
+    Some(format!("Argument should be {}", expected))
+}
+
+/// Lists faulty conditions of a specification with optics or XPaths as `field`s
+pub(super) fn conditions_problems(field: &str, conds: &Value) -> Vec<ConditionProblem> {
+    match conds {
+        Value::Object(conds) => conds.iter()
+            .filter_map(|(kwd, etalon)| {
+                let reason = match serde_json::from_value::<Keyword>(Value::String(kwd.clone())) {
+                    Ok(keyword) => condition_problem(&keyword, etalon)?,
+                    Err(_) => "Unknown keyword".to_string()
+                };
+
+                Some(ConditionProblem { field: field.to_string(), keyword: Some(kwd.clone()), value: etalon.clone(), reason })
+            })
+            .collect(),
+        other => vec![ConditionProblem {
+            field: field.to_string(),
+            keyword: None,
+            value: other.clone(),
+            reason: "Conditions should be an object".to_string()
+        }]
     }
 }
 
+fn spec_problems(spec: &Value, location: &str) -> Vec<ConditionProblem> {
+    let spec = match spec {
+        Value::Object(spec) => spec,
+        other => return vec![ConditionProblem {
+            field: location.trim_end_matches('.').to_string(),
+            keyword: None,
+            value: other.clone(),
+            reason: "Predicate should be an object".to_string()
+        }]
+    };
+
+    let mut problems = vec![];
+
+    for (field, conds) in spec.iter() {
+        match nested_specs(field, conds) {
+            Some(Ok(nested)) => nested.into_iter()
+                .for_each(|(nested_location, nested_spec)| problems.extend(spec_problems(nested_spec, &format!("{}{}.", location, nested_location)))),
+            Some(Err(reason)) => problems.push(ConditionProblem {
+                field: format!("{}{}", location, field),
+                keyword: None,
+                value: conds.clone(),
+                reason
+            }),
+            None => problems.extend(conditions_problems(&format!("{}{}", location, field), conds))
+        }
+    }
+
+    problems
+}
+
+/// Faulty condition of a predicate specification
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ConditionProblem {
+    /// Optic or XPath, conditions of nested predicates are prefixed with their group, e.g. `$or[1].a`
+    pub field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<String>,
+    pub value: Value,
+    pub reason: String
+}
+
 pub struct PredicateConstructionError<'r> {
     pub problems: Vec<Condition<'r>>
 }
 
-enum ValidationError<'r> {
+pub(super) enum ValidationError<'r> {
     ConditionError {
         keyword: &'r Keyword,
         argument: &'r Value
@@ -141,10 +263,7 @@ enum ValidationError<'r> {
 
 impl ValidationError<'_> {
     fn is_data_error(&self) -> bool {
-        match self {
-            ValidationError::DataError => true,
-            _ => false
-        }
+        matches!(self, ValidationError::DataError)
     }
 }
 
@@ -372,4 +491,186 @@ mod json_tests {
         assert!(predicate.validate(json!({"f": [2, "1", true]})).ok().unwrap());
         assert!(!predicate.validate(json!({"f": [2, "1", false]})).ok().unwrap());
     }
+
+    #[test]
+    fn check_any_of_traversed() {
+        let json_spec: Value = json!({"items.$.sku": {"any": {"==": "X"}}});
+        let predicate = serde_json::from_value::<JsonPredicate>(json_spec).ok().unwrap();
+
+        assert!(predicate.validate(json!({"items": [{"sku": "X"}]})).ok().unwrap());
+        assert!(predicate.validate(json!({"items": [{"sku": "A"}, {"sku": "X"}]})).ok().unwrap());
+        assert!(!predicate.validate(json!({"items": [{"sku": "A"}, {"sku": "B"}]})).ok().unwrap());
+        assert!(!predicate.validate(json!({"items": []})).ok().unwrap());
+        assert!(!predicate.validate(json!({})).ok().unwrap());
+    }
+
+    #[test]
+    fn check_all_of_traversed() {
+        let json_spec: Value = json!({"items.$.qty": {"all": {">": 0, "<=": 10}}});
+        let predicate = serde_json::from_value::<JsonPredicate>(json_spec).ok().unwrap();
+
+        assert!(predicate.validate(json!({"items": [{"qty": 1}, {"qty": 10}]})).ok().unwrap());
+        assert!(!predicate.validate(json!({"items": [{"qty": 1}, {"qty": 0}]})).ok().unwrap());
+        assert!(!predicate.validate(json!({"items": [{"qty": 1}, {"qty": "2"}]})).ok().unwrap());
+        assert!(predicate.validate(json!({"items": []})).ok().unwrap());
+    }
+
+    #[test]
+    fn plain_conditions_should_check_first_traversed_value() {
+        let json_spec: Value = json!({"items.$.sku": {"==": "X"}});
+        let predicate = serde_json::from_value::<JsonPredicate>(json_spec).ok().unwrap();
+
+        assert!(predicate.validate(json!({"items": [{"sku": "X"}, {"sku": "A"}]})).ok().unwrap());
+        assert!(!predicate.validate(json!({"items": [{"sku": "A"}, {"sku": "X"}]})).ok().unwrap());
+    }
+
+    #[test]
+    fn quantifiers_should_not_accept_faulty_conditions() {
+        assert!(serde_json::from_value::<JsonPredicate>(json!({"items.$": {"any": {">": "1"}}})).is_err());
+        assert!(serde_json::from_value::<JsonPredicate>(json!({"items.$": {"any": {"all": {"==": 1}}}})).is_err());
+        assert!(serde_json::from_value::<JsonPredicate>(json!({"items.$": {"all": {}}})).is_err());
+        assert!(serde_json::from_value::<JsonPredicate>(json!({"items.$": {"all": 1}})).is_err());
+    }
+
+    #[test]
+    fn check_starts_with() {
+        let json_spec: Value = json!({"f": {"^": "pe"}});
+        let predicate = serde_json::from_value::<JsonPredicate>(json_spec).ok().unwrap();
+
+        assert!(predicate.validate(json!({"f": "peka"})).ok().unwrap());
+        assert!(predicate.validate(json!({"f": "pe"})).ok().unwrap());
+        assert!(!predicate.validate(json!({"f": "Peka"})).ok().unwrap());
+        assert!(!predicate.validate(json!({"f": ["peka"]})).ok().unwrap());
+        assert!(!predicate.validate(json!({})).ok().unwrap());
+    }
+
+    #[test]
+    fn check_equality_ignoring_case() {
+        let json_spec: Value = json!({"f": {"==i": "PeKa"}});
+        let predicate = serde_json::from_value::<JsonPredicate>(json_spec).ok().unwrap();
+
+        assert!(predicate.validate(json!({"f": "peka"})).ok().unwrap());
+        assert!(predicate.validate(json!({"f": "PEKA"})).ok().unwrap());
+        assert!(!predicate.validate(json!({"f": "pekas"})).ok().unwrap());
+        assert!(!predicate.validate(json!({"f": 1})).ok().unwrap());
+    }
+
+    #[test]
+    fn check_contains() {
+        let json_spec: Value = json!({"f": {"contains": "ek"}});
+        let predicate = serde_json::from_value::<JsonPredicate>(json_spec).ok().unwrap();
+
+        assert!(predicate.validate(json!({"f": "peka"})).ok().unwrap());
+        assert!(!predicate.validate(json!({"f": "pka"})).ok().unwrap());
+        assert!(!predicate.validate(json!({"f": ["ek"]})).ok().unwrap());
+    }
+
+    #[test]
+    fn check_size_range() {
+        let json_spec: Value = json!({"f": {"size>": 1, "size<": 4}});
+        let predicate = serde_json::from_value::<JsonPredicate>(json_spec).ok().unwrap();
+
+        assert!(predicate.validate(json!({"f": "12"})).ok().unwrap());
+        assert!(predicate.validate(json!({"f": [1, 2, 3]})).ok().unwrap());
+        assert!(!predicate.validate(json!({"f": "1"})).ok().unwrap());
+        assert!(!predicate.validate(json!({"f": [1, 2, 3, 4]})).ok().unwrap());
+        assert!(!predicate.validate(json!({"f": 2})).ok().unwrap());
+    }
+
+    #[test]
+    fn check_object_keys() {
+        let any_key = serde_json::from_value::<JsonPredicate>(json!({"f": {"{_}": ["a", "b"]}})).ok().unwrap();
+        let no_key = serde_json::from_value::<JsonPredicate>(json!({"f": {"!{_}": ["a", "b"]}})).ok().unwrap();
+        let all_keys = serde_json::from_value::<JsonPredicate>(json!({"f": {"&{_}": ["a", "b"]}})).ok().unwrap();
+
+        assert!(any_key.validate(json!({"f": {"a": 1, "c": 3}})).ok().unwrap());
+        assert!(!any_key.validate(json!({"f": {"c": 3}})).ok().unwrap());
+        assert!(!any_key.validate(json!({"f": ["a"]})).ok().unwrap());
+
+        assert!(no_key.validate(json!({"f": {"c": 3}})).ok().unwrap());
+        assert!(!no_key.validate(json!({"f": {"b": null}})).ok().unwrap());
+
+        assert!(all_keys.validate(json!({"f": {"a": 1, "b": 2, "c": 3}})).ok().unwrap());
+        assert!(!all_keys.validate(json!({"f": {"a": 1}})).ok().unwrap());
+
+        assert!(serde_json::from_value::<JsonPredicate>(json!({"f": {"{_}": ["a", 1]}})).is_err());
+    }
+
+    #[test]
+    fn check_or() {
+        let json_spec: Value = json!({"$or": [{"a": {"==": "X"}}, {"b": {"==": "Y"}}]});
+        let predicate = serde_json::from_value::<JsonPredicate>(json_spec).ok().unwrap();
+
+        assert!(predicate.validate(json!({"a": "X", "b": "Z"})).ok().unwrap());
+        assert!(predicate.validate(json!({"a": "Z", "b": "Y"})).ok().unwrap());
+        assert!(!predicate.validate(json!({"a": "Z", "b": "Z"})).ok().unwrap());
+    }
+
+    #[test]
+    fn check_not() {
+        let json_spec: Value = json!({"a": {"exists": true}, "$not": {"a": {"[_]": [1, 2]}}});
+        let predicate = serde_json::from_value::<JsonPredicate>(json_spec).ok().unwrap();
+
+        assert!(predicate.validate(json!({"a": 3})).ok().unwrap());
+        assert!(predicate.validate(json!({"a": "1"})).ok().unwrap());
+        assert!(!predicate.validate(json!({"a": 1})).ok().unwrap());
+        assert!(!predicate.validate(json!({})).ok().unwrap());
+    }
+
+    #[test]
+    fn check_nested_groups() {
+        let json_spec: Value = json!({
+            "kind": {"==": "order"},
+            "$and": [
+                {"$or": [{"amount": {">": 100}}, {"vip": {"==": true}}]},
+                {"$not": {"status": {"==": "closed"}}}
+            ]
+        });
+        let predicate = serde_json::from_value::<JsonPredicate>(json_spec.clone()).ok().unwrap();
+
+        assert!(predicate.validate(json!({"kind": "order", "amount": 200, "status": "new"})).ok().unwrap());
+        assert!(predicate.validate(json!({"kind": "order", "amount": 1, "vip": true})).ok().unwrap());
+        assert!(!predicate.validate(json!({"kind": "order", "amount": 1})).ok().unwrap());
+        assert!(!predicate.validate(json!({"kind": "order", "amount": 200, "status": "closed"})).ok().unwrap());
+        assert!(!predicate.validate(json!({"kind": "refund", "amount": 200})).ok().unwrap());
+
+        assert_eq!(serde_json::to_value(&predicate).unwrap(), json_spec);
+    }
+
+    #[test]
+    fn faulty_groups_should_be_rejected() {
+        assert!(serde_json::from_value::<JsonPredicate>(json!({"$or": []})).is_err());
+        assert!(serde_json::from_value::<JsonPredicate>(json!({"$and": {"a": {"==": 1}}})).is_err());
+        assert_eq!(
+            serde_json::from_value::<JsonPredicate>(json!({"$not": {"a": {">": "1"}}})).err().unwrap().to_string(),
+            "Conditions are faulty on fields: a"
+        );
+    }
+
+    #[test]
+    fn problems_should_describe_faulty_conditions() {
+        let spec = json!({
+            "a": {"~=": "(", "==": 1},
+            "$or": [{"b": {">": "1"}}, {"c": {"size": 2, "in": [1]}}],
+            "$not": {"d": []},
+            "e.$": {"any": {"all": {}}}
+        });
+
+        let mut problems = JsonPredicate::problems(&spec).into_iter()
+            .map(|p| (p.field, p.keyword, p.value, p.reason))
+            .collect::<Vec<_>>();
+        problems.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(problems.len(), 5);
+        assert_eq!(problems[0].0, "$not.d");
+        assert_eq!(problems[0].3, "Conditions should be an object");
+        assert_eq!(problems[1], ("$or[0].b".to_string(), Some(">".to_string()), json!("1"), "Argument should be a number".to_string()));
+        assert_eq!(problems[2], ("$or[1].c".to_string(), Some("in".to_string()), json!([1]), "Unknown keyword".to_string()));
+        assert_eq!(problems[3].0, "a");
+        assert!(problems[3].3.starts_with("Invalid regex"));
+        assert_eq!(problems[4], ("e.$".to_string(), Some("any".to_string()), json!({"all": {}}), "Quantifier 'all' can't be nested".to_string()));
+
+        assert!(JsonPredicate::problems(&json!({"a": {"==": 1}, "$and": [{"b": {"exists": true}}]})).is_empty());
+        assert_eq!(JsonPredicate::problems(&json!({"$or": []}))[0].field, "$or");
+    }
 }
\ No newline at end of file
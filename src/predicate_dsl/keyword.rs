@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
 pub enum Keyword {
     #[serde(rename = "==")]
     Equals,
@@ -25,5 +25,25 @@ pub enum Keyword {
     #[serde(rename = "![_]")]
     NotIn,
     #[serde(rename = "&[_]")]
-    AllIn
+    AllIn,
+    #[serde(rename = "^")]
+    StartsWith,
+    #[serde(rename = "==i")]
+    EqualsIgnoreCase,
+    #[serde(rename = "contains")]
+    Contains,
+    #[serde(rename = "size>")]
+    SizeGreater,
+    #[serde(rename = "size<")]
+    SizeLess,
+    #[serde(rename = "{_}")]
+    KeyIn,
+    #[serde(rename = "!{_}")]
+    KeyNotIn,
+    #[serde(rename = "&{_}")]
+    AllKeysIn,
+    #[serde(rename = "any")]
+    Any,
+    #[serde(rename = "all")]
+    All
 }
\ No newline at end of file
@@ -0,0 +1,261 @@
+use crate::predicate_dsl::condition::CompiledCondition;
+use crate::predicate_dsl::json::{combine_results, conditions_problems, ConditionProblem, PredicateConstructionError, ValidationError};
+use crate::predicate_dsl::keyword::Keyword;
+use crate::utils::xml::CompiledXPath;
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+use serde::de::Error;
+use serde_json::{Number, Value};
+use sxd_document::dom::Document;
+use sxd_xpath::Value as XValue;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+type Spec = HashMap<String, HashMap<Keyword, Value>>;
+
+/// Same conditions as [JsonPredicate], keyed by XPath expressions
+#[derive(Default)]
+pub struct XmlPredicate {
+    definition: Spec,
+    compiled: Vec<(CompiledXPath, Vec<CompiledCondition>)>
+}
+
+impl XmlPredicate {
+    /// Node sets become a single value if there is one node, an array of values otherwise.
+    /// Node text is interpreted according to the type of the condition argument
+    pub fn validate(&self, document: &Document<'_>, namespaces: &HashMap<String, String>) -> Result<bool, PredicateConstructionError<'_>> {
+        let mut result: Vec<Result<bool, ValidationError<'_>>> = vec![];
+
+        for (xpath, conds) in self.compiled.iter() {
+            let found = xpath.evaluate(document, namespaces);
+
+            for cond in conds.iter() {
+                result.push(match &found {
+                    Some(value) => cond.validate(&to_json(value, &cond.keyword, &cond.argument)),
+                    None => Err(ValidationError::DataError)
+                });
+            }
+        }
+
+        combine_results(result)
+    }
+
+    /// Lists faulty conditions of the specification, it can be deserialized only if there are none
+    pub fn problems(spec: &Value) -> Vec<ConditionProblem> {
+        let spec = match spec {
+            Value::Object(spec) => spec,
+            other => return vec![ConditionProblem {
+                field: String::new(),
+                keyword: None,
+                value: other.clone(),
+                reason: "Predicate should be an object".to_string()
+            }]
+        };
+
+        let mut problems = vec![];
+
+        for (xpath, conds) in spec.iter() {
+            if CompiledXPath::compile(xpath).is_none() {
+                problems.push(ConditionProblem {
+                    field: xpath.clone(),
+                    keyword: None,
+                    value: conds.clone(),
+                    reason: "Invalid XPath expression".to_string()
+                });
+                continue;
+            }
+
+            // quantifiers are reported regardless of their arguments
+            let mut found = conditions_problems(xpath, conds);
+            found.retain(|problem| !matches!(problem.keyword.as_deref(), Some("any" | "all")));
+
+            if let Value::Object(conds) = conds {
+                for (kwd, etalon) in conds.iter().filter(|(kwd, _)| matches!(kwd.as_str(), "any" | "all")) {
+                    found.push(ConditionProblem {
+                        field: xpath.clone(),
+                        keyword: Some(kwd.clone()),
+                        value: etalon.clone(),
+                        reason: "Quantifiers are not supported".to_string()
+                    });
+                }
+            }
+
+            problems.extend(found);
+        }
+
+        problems
+    }
+}
+
+fn to_json(value: &XValue<'_>, kwd: &Keyword, etalon: &Value) -> Value {
+    match value {
+        XValue::Boolean(b) => Value::Bool(*b),
+        XValue::Number(n) => number(*n),
+        XValue::String(s) => typed(s, kwd, etalon),
+        XValue::Nodeset(nodes) => {
+            let mut values = nodes.document_order().iter().map(|node| typed(&node.string_value(), kwd, etalon)).collect::<Vec<_>>();
+
+            match values.len() {
+                0 => Value::Null,
+                1 => values.remove(0),
+                _ => Value::Array(values)
+            }
+        }
+    }
+}
+
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Value::from(n as i64)
+    } else {
+        Number::from_f64(n).map(Value::Number).unwrap_or(Value::Null)
+    }
+}
+
+/// XML has no types, so text is converted to the type of the condition argument when possible
+fn typed(text: &str, kwd: &Keyword, etalon: &Value) -> Value {
+    let sample = match (kwd, etalon) {
+        (Keyword::Rx | Keyword::Size | Keyword::SizeGreater | Keyword::SizeLess | Keyword::Exists, _) =>
+            return Value::String(text.to_string()),
+        (Keyword::In | Keyword::NotIn | Keyword::AllIn, Value::Array(vs)) => vs.first().unwrap_or(&Value::Null),
+        (_, other) => other
+    };
+
+    let converted = match sample {
+        Value::Number(_) => text.trim().parse::<Number>().ok().map(Value::Number),
+        Value::Bool(_) => text.trim().parse::<bool>().ok().map(Value::Bool),
+        _ => None
+    };
+
+    converted.unwrap_or_else(|| Value::String(text.to_string()))
+}
+
+impl Serialize for XmlPredicate {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.definition.serialize(serializer)
+    }
+}
+
+impl <'de> Deserialize<'de> for XmlPredicate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let spec = Spec::deserialize(deserializer)?;
+
+        let mut faulty_fields: Vec<String> = vec![];
+        let mut compiled = vec![];
+
+        for (xpath, cond) in spec.iter() {
+            // quantifiers are not supported, node sets are compared as arrays
+            let conds = cond.iter()
+                .filter(|(kwd, _)| !matches!(kwd, Keyword::Any | Keyword::All))
+                .filter_map(|(kwd, v)| CompiledCondition::compile(kwd, v))
+                .collect::<Vec<_>>();
+
+            match CompiledXPath::compile(xpath) {
+                Some(built) if conds.len() == cond.len() => compiled.push((built, conds)),
+                _ => faulty_fields.push(xpath.clone())
+            }
+        }
+
+        if !faulty_fields.is_empty() {
+            Err(D::Error::custom(format!("Conditions are faulty on fields: {}", faulty_fields.join(", "))))
+        } else {
+            Ok(XmlPredicate { definition: spec, compiled })
+        }
+    }
+}
+
+impl Debug for XmlPredicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            serde_json::to_string(&self.definition).expect("Unserializable XmlPredicate!")
+        )
+    }
+}
+
+#[cfg(test)]
+mod xml_tests {
+    use crate::predicate_dsl::xml::XmlPredicate;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use sxd_document::parser;
+
+    const ORDER: &str = r#"
+        <s:Envelope xmlns:s="urn:soap">
+            <s:Body>
+                <order id="42" express="true">
+                    <customer>peka</customer>
+                    <item>pen</item>
+                    <item>book</item>
+                    <total>10.5</total>
+                </order>
+            </s:Body>
+        </s:Envelope>"#;
+
+    fn check(spec: serde_json::Value, namespaces: &HashMap<String, String>) -> bool {
+        let predicate = serde_json::from_value::<XmlPredicate>(spec).unwrap();
+        let package = parser::parse(ORDER).unwrap();
+
+        predicate.validate(&package.as_document(), namespaces).ok().unwrap()
+    }
+
+    #[test]
+    fn xml_predicate_should_emit_correct_error_for_poor_specification() {
+        let predicate = serde_json::from_value::<XmlPredicate>(json!({"/order/total": {">=": "test"}}));
+        assert_eq!(predicate.err().unwrap().to_string(), "Conditions are faulty on fields: /order/total");
+
+        let predicate = serde_json::from_value::<XmlPredicate>(json!({"/order[": {"==": "test"}}));
+        assert_eq!(predicate.err().unwrap().to_string(), "Conditions are faulty on fields: /order[");
+
+        let predicate = serde_json::from_value::<XmlPredicate>(json!({"//item": {"any": {"==": "pen"}}}));
+        assert_eq!(predicate.err().unwrap().to_string(), "Conditions are faulty on fields: //item");
+    }
+
+    #[test]
+    fn check_equality_and_comparison() {
+        let no_ns = HashMap::new();
+
+        assert!(check(json!({
+            "//order/@id": {"==": 42},
+            "//order/@express": {"==": true},
+            "//customer": {"==": "peka", "~=": "^p.+a$"},
+            "//total": {">": 10, "<=": 10.5},
+            "count(//item)": {"==": 2}
+        }), &no_ns));
+
+        assert!(!check(json!({"//customer": {"==": "name"}}), &no_ns));
+        assert!(!check(json!({"//order/@id": {">": 100}}), &no_ns));
+        assert!(!check(json!({"//customer": {">": 100}}), &no_ns));
+    }
+
+    #[test]
+    fn check_node_sets() {
+        let no_ns = HashMap::new();
+
+        assert!(check(json!({
+            "//item": {"&[_]": ["book", "pen"], "size": 2},
+            "//customer": {"[_]": ["peka", "name"]},
+            "//address": {"exists": false}
+        }), &no_ns));
+
+        assert!(!check(json!({"//item": {"![_]": ["book"]}}), &no_ns));
+    }
+
+    #[test]
+    fn check_namespaces() {
+        let namespaces = HashMap::from([("soap".to_string(), "urn:soap".to_string())]);
+
+        assert!(check(json!({"/soap:Envelope/soap:Body/order/customer": {"==": "peka"}}), &namespaces));
+        assert!(!check(json!({"/soap:Envelope/soap:Body/order/customer": {"==": "peka"}}), &HashMap::new()));
+    }
+
+    #[test]
+    fn problems_should_describe_faulty_conditions() {
+        let problems = XmlPredicate::problems(&json!({"/order[": {"==": 1}, "//item": {"any": {"==": "pen"}}, "//total": {">": "1"}}));
+
+        assert_eq!(problems.len(), 3);
+        assert!(problems.iter().any(|p| p.field == "/order[" && p.reason == "Invalid XPath expression"));
+        assert!(problems.iter().any(|p| p.field == "//item" && p.keyword.as_deref() == Some("any") && p.reason == "Quantifiers are not supported"));
+        assert!(problems.iter().any(|p| p.field == "//total" && p.value == json!("1") && p.reason == "Argument should be a number"));
+    }
+}
\ No newline at end of file
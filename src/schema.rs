@@ -10,6 +10,27 @@ pub mod sql_types {
     pub struct Scope;
 }
 
+diesel::table! {
+    blob (id) {
+        id -> Int4,
+        created -> Timestamptz,
+        #[max_length = 256]
+        name -> Varchar,
+        #[max_length = 256]
+        content_type -> Varchar,
+        content -> Bytea,
+    }
+}
+
+diesel::table! {
+    service (suffix) {
+        #[max_length = 40]
+        suffix -> Varchar,
+        #[max_length = 40]
+        name -> Varchar,
+    }
+}
+
 diesel::table! {
     state (id) {
         id -> Int4,
@@ -47,6 +68,8 @@ diesel::table! {
 }
 
 diesel::allow_tables_to_appear_in_same_query!(
+    blob,
+    service,
     state,
     stub,
 );
@@ -1,8 +1,11 @@
 use bigdecimal::BigDecimal;
 
+pub mod form;
 pub mod js;
 pub mod pg;
+pub mod serde_base64;
 pub mod transformations;
+pub mod xml;
 
 pub trait IntoBD {
     fn to_big_decimal(self) -> BigDecimal;
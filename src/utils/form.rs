@@ -0,0 +1,134 @@
+use crate::error::Error;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Part of a `multipart/form-data` body. Header names are lowercase
+pub struct Part {
+    pub name: String,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>
+}
+
+/// Converts `application/x-www-form-urlencoded` data into a JSON object,
+/// values of repeated fields are collected into arrays
+pub fn urlencoded_to_json(data: &str) -> Value {
+    let fields = serde_urlencoded::from_str::<Vec<(String, String)>>(data).unwrap_or_default();
+
+    let mut res = Map::new();
+
+    for (name, value) in fields {
+        match res.get_mut(&name) {
+            Some(Value::Array(values)) => values.push(Value::String(value)),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), Value::String(value)]),
+            None => {
+                res.insert(name, Value::String(value));
+            }
+        }
+    }
+
+    Value::Object(res)
+}
+
+/// Splits `multipart/form-data` body into parts using the boundary from `content_type`
+pub fn parse_multipart(body: &[u8], content_type: &str) -> Result<Vec<Part>, Error> {
+    let boundary = content_type.split(';')
+        .filter_map(|param| param.trim().strip_prefix("boundary="))
+        .map(|b| b.trim_matches('"'))
+        .next()
+        .ok_or_else(|| Error::new(format!("No boundary in '{}'", content_type)))?;
+
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut pos = find(body, &delimiter, 0).ok_or_else(|| Error::new("Multipart body has no parts".to_string()))? + delimiter.len();
+    let mut parts = vec![];
+
+    // each delimiter is followed either by CRLF and a part, or by `--` at the end of the body
+    while !body[pos..].starts_with(b"--") {
+        let start = pos + crlf_len(&body[pos..]);
+        let end = find(body, &delimiter, start).ok_or_else(|| Error::new("Multipart body is not terminated".to_string()))?;
+
+        // delimiter is preceded by CRLF which is not a part of the body
+        let content = &body[start..end];
+        let content = content.strip_suffix(b"\r\n").or_else(|| content.strip_suffix(b"\n")).unwrap_or(content);
+
+        parts.push(parse_part(content)?);
+
+        pos = end + delimiter.len();
+    }
+
+    Ok(parts)
+}
+
+fn parse_part(content: &[u8]) -> Result<Part, Error> {
+    let (head, body) = match find(content, b"\r\n\r\n", 0) {
+        Some(split) => (&content[..split], &content[split + 4..]),
+        None => match find(content, b"\n\n", 0) {
+            Some(split) => (&content[..split], &content[split + 2..]),
+            None => (content, &content[content.len()..])
+        }
+    };
+
+    let headers = String::from_utf8_lossy(head).lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect::<HashMap<_, _>>();
+
+    let name = headers.get("content-disposition")
+        .and_then(|disposition| disposition.split(';').filter_map(|param| param.trim().strip_prefix("name=")).next())
+        .map(|name| name.trim_matches('"').to_string())
+        .ok_or_else(|| Error::new("Multipart part has no name".to_string()))?;
+
+    Ok(Part { name, headers, body: body.to_vec() })
+}
+
+fn crlf_len(data: &[u8]) -> usize {
+    if data.starts_with(b"\r\n") {
+        2
+    } else if data.starts_with(b"\n") {
+        1
+    } else {
+        0
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack.get(from..)?.windows(needle.len()).position(|window| window == needle).map(|pos| from + pos)
+}
+
+#[cfg(test)]
+mod form_tests {
+    use crate::utils::form::{parse_multipart, urlencoded_to_json};
+    use serde_json::json;
+
+    #[test]
+    fn urlencoded_data_should_be_converted_to_json_object() {
+        assert_eq!(urlencoded_to_json("a=1&b=some+text&a=2"), json!({"a": ["1", "2"], "b": "some text"}));
+        assert_eq!(urlencoded_to_json(""), json!({}));
+    }
+
+    #[test]
+    fn multipart_body_should_be_split_into_parts() {
+        let body = b"preamble\r\n--XyZ\r\n\
+            Content-Disposition: form-data; name=\"meta\"\r\n\
+            Content-Type: application/json\r\n\r\n\
+            {\"id\": 1}\r\n\
+            --XyZ\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"a.bin\"\r\n\r\n\
+            \x00\xff\r\n\x01\r\n\
+            --XyZ--\r\n";
+
+        let parts = parse_multipart(body, "multipart/form-data; boundary=\"XyZ\"").unwrap();
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].name, "meta");
+        assert_eq!(parts[0].headers.get("content-type").map(String::as_str), Some("application/json"));
+        assert_eq!(parts[0].body, b"{\"id\": 1}");
+        assert_eq!(parts[1].name, "file");
+        assert_eq!(parts[1].body, b"\x00\xff\r\n\x01");
+    }
+
+    #[test]
+    fn malformed_multipart_should_be_rejected() {
+        assert!(parse_multipart(b"--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n1", "multipart/form-data; boundary=XyZ").is_err());
+        assert!(parse_multipart(b"--XyZ--", "multipart/form-data").is_err());
+    }
+}
\ No newline at end of file
@@ -8,9 +8,9 @@ pub mod optic;
 
 impl IntoBD for &Number {
     fn to_big_decimal(self) -> BigDecimal {
-        self.as_i64().map(|i| BigDecimal::from(i))
-            .or(self.as_u64().map(|u| BigDecimal::from(u)))
-            .or(self.as_f64().and_then(|f| BigDecimal::from_f64(f)))
+        self.as_i64().map(BigDecimal::from)
+            .or(self.as_u64().map(BigDecimal::from))
+            .or(self.as_f64().and_then(BigDecimal::from_f64))
             .unwrap_or(BigDecimal::zero())
     }
 }
@@ -37,10 +37,7 @@ pub enum Jsn {
 
 impl Jsn {
     pub fn is_string(&self) -> bool {
-        match self {
-            Jsn::String(_) => true,
-            _ => false
-        }
+        matches!(self, Jsn::String(_))
     }
 }
 
@@ -69,7 +66,7 @@ impl From<Value> for Jsn {
 impl Display for Jsn {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Jsn::Null => write!(fmt, "{}", "null"),
+            Jsn::Null => write!(fmt, "null"),
             Jsn::Bool(b) => write!(fmt, "{}", b),
             Jsn::Signed(i) => write!(fmt, "{}", i),
             Jsn::Float(f) => write!(fmt, "{}", f),
@@ -93,19 +90,19 @@ impl Debug for Jsn {
 }
 
 trait ValueExtInternal {
-    fn modify_field_in_place(&mut self, name: &String, modify: impl Fn(&mut Value) -> (), default: impl Fn() -> Value);
-    fn modify_position_in_place(&mut self, index: usize, modify: impl Fn(&mut Value) -> (), default: impl Fn() -> Value);
-    fn traverse_in_place(&mut self, modify: impl Fn(&mut Value) -> (), default: impl Fn() -> Value);
-    fn verify_field(&self, name: &String) -> bool;
+    fn modify_field_in_place(&mut self, name: &str, modify: impl Fn(&mut Value), default: impl Fn() -> Value);
+    fn modify_position_in_place(&mut self, index: usize, modify: impl Fn(&mut Value), default: impl Fn() -> Value);
+    fn traverse_in_place(&mut self, modify: impl Fn(&mut Value), default: impl Fn() -> Value);
+    fn verify_field(&self, name: &str) -> bool;
     fn verify_position(&self, index: usize) -> bool;
-    fn field(&self, field_name: &String) -> Option<&Value>;
+    fn field(&self, field_name: &str) -> Option<&Value>;
     fn at_index(&self, index: usize) -> Option<&Value>;
-    fn remove_field(&mut self, field_name: &String);
+    fn remove_field(&mut self, field_name: &str);
     fn remove_at_index(&mut self, index: usize);
 }
 
 impl ValueExtInternal for Value {
-    fn modify_field_in_place(&mut self, name: &String, modify: impl Fn(&mut Value) -> (), default: impl Fn() -> Value) {
+    fn modify_field_in_place(&mut self, name: &str, modify: impl Fn(&mut Value), default: impl Fn() -> Value) {
         match self {
             Value::Object(jo) => {
                 if let Some(jv) = jo.get_mut(name) {
@@ -113,7 +110,7 @@ impl ValueExtInternal for Value {
                 } else {
                     let mut new_val = default();
                     modify(&mut new_val);
-                    jo.insert(name.clone(), new_val);
+                    jo.insert(name.to_string(), new_val);
                 }
             }
             _ => {
@@ -124,7 +121,7 @@ impl ValueExtInternal for Value {
         }
     }
 
-    fn modify_position_in_place(&mut self, idx: usize, modify: impl Fn(&mut Value) -> (), default: impl Fn() -> Value) {
+    fn modify_position_in_place(&mut self, idx: usize, modify: impl Fn(&mut Value), default: impl Fn() -> Value) {
         match self {
             Value::Array(ja) => {
                 if ja.len() <= idx {
@@ -146,7 +143,7 @@ impl ValueExtInternal for Value {
         }
     }
 
-    fn traverse_in_place(&mut self, modify: impl Fn(&mut Value) -> (), default: impl Fn() -> Value) {
+    fn traverse_in_place(&mut self, modify: impl Fn(&mut Value), default: impl Fn() -> Value) {
         match self {
             Value::Array(ja) => {
                 for jv in ja.iter_mut() {
@@ -161,7 +158,7 @@ impl ValueExtInternal for Value {
         }
     }
 
-    fn verify_field(&self, name: &String) -> bool {
+    fn verify_field(&self, name: &str) -> bool {
         self.as_object().map(|m| m.contains_key(name)).unwrap_or(false)
     }
 
@@ -169,7 +166,7 @@ impl ValueExtInternal for Value {
         self.as_array().map(|a| a.len() > idx).unwrap_or(false)
     }
 
-    fn field(&self, field_name: &String) -> Option<&Value> {
+    fn field(&self, field_name: &str) -> Option<&Value> {
         match self {
             Value::Object(map) => map.get(field_name),
             _ => None,
@@ -183,7 +180,7 @@ impl ValueExtInternal for Value {
         }
     }
 
-    fn remove_field(&mut self, field_name: &String) {
+    fn remove_field(&mut self, field_name: &str) {
         if let Some(jmap) = self.as_object_mut() {
             jmap.remove(field_name);
         }
@@ -70,6 +70,12 @@ impl JsonOptic {
         self
     }
 
+    /// Checks that optic is not empty and has no blank field names
+    pub fn is_well_formed(&self) -> bool {
+        !self.json_path.is_empty()
+            && self.json_path.iter().all(|part| !matches!(part, PathPart::Field(name) if name.is_empty()))
+    }
+
     /// Renders JsonOptic into a JsonPath-compatible representation
     pub fn to_json_path_string(&self) -> String {
         format!(
@@ -77,7 +83,7 @@ impl JsonOptic {
             self.json_path
                 .iter()
                 .map(|part| match part {
-                    PathPart::Field(f) => format!("{}", f),
+                    PathPart::Field(f) => f.to_string(),
                     PathPart::Index(i) => format!("[{}]", i),
                     PathPart::Traverse => "[*]".to_string()
                 })
@@ -144,16 +150,13 @@ impl ValueExt for Value {
             Box::new(move |arg: &mut Value| {
                 arg.modify_part_in_place(
                     el,
-                    |v_ref| {
-                        acc(v_ref);
-                        ()
-                    },
+                    |v_ref| acc(v_ref),
                     || Value::Null,
                 );
             })
         });
 
-        let _ = modify_fn(self);
+        modify_fn(self);
     }
 
     fn set_opt(&mut self, optic: &JsonOptic, v: Option<&Value>) {
@@ -181,16 +184,13 @@ impl ValueExt for Value {
                     Box::new(move |arg: &mut Value| {
                         arg.modify_part_in_place(
                             el,
-                            |v_ref| {
-                                acc(v_ref);
-                                ()
-                            },
+                            |v_ref| acc(v_ref),
                             || Value::Null,
                         )
                     })
                 });
 
-            let _ = modify_fn(self);
+            modify_fn(self);
         }
     }
 
@@ -228,7 +228,7 @@ impl ValueExt for Value {
 }
 
 trait ValueExtSugar {
-    fn modify_part_in_place(&mut self, part: &PathPart, modify: impl Fn(&mut Value) -> (), default: impl Fn() -> Value);
+    fn modify_part_in_place(&mut self, part: &PathPart, modify: impl Fn(&mut Value), default: impl Fn() -> Value);
     fn verify(&self, part: &PathPart) -> bool;
 }
 
@@ -236,7 +236,7 @@ impl ValueExtSugar for Value {
     fn modify_part_in_place(
         &mut self,
         part: &PathPart,
-        modify: impl Fn(&mut Value) -> (),
+        modify: impl Fn(&mut Value),
         default: impl Fn() -> Value,
     ) {
         match part {
@@ -390,6 +390,15 @@ mod optic_tests {
         assert_eq!("outer.inner", optic.ok().unwrap().to_string())
     }
 
+    #[test]
+    fn json_optic_should_detect_blank_fields() {
+        assert!(JsonOptic::from_path("outer.inner").is_well_formed());
+        assert!(JsonOptic::from_path("outer.[0].$").is_well_formed());
+        assert!(!JsonOptic::from_path("").is_well_formed());
+        assert!(!JsonOptic::from_path("outer..inner").is_well_formed());
+        assert!(!JsonOptic::from_path("outer.").is_well_formed());
+    }
+
     #[test]
     fn json_optic_corretly_renders_into_jsonpath() {
         let optic1 = serde_json::from_value::<JsonOptic>(json!("track.segments.[0].location")).ok().unwrap();
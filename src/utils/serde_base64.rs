@@ -0,0 +1,15 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Deserializer, Serializer};
+use serde::de::Error;
+
+/// Serializes bytes as a base64 string, to be used with `#[serde(with = "...")]`
+pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+    serializer.serialize_str(&STANDARD.encode(bytes))
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error> where D: Deserializer<'de> {
+    let encoded = String::deserialize(deserializer)?;
+
+    STANDARD.decode(encoded.trim()).map_err(|e| D::Error::custom(format!("Incorrect base64: {}", e)))
+}
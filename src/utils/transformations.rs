@@ -1 +1,2 @@
+pub mod generators;
 pub mod js;
\ No newline at end of file
@@ -0,0 +1,208 @@
+use crate::error::Error;
+use chrono::{Local, Utc};
+use rand::Rng;
+use rand::distributions::{Alphanumeric, DistString};
+use regex::{Captures, Regex};
+use serde_json::Value;
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+static GENERATOR_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"%\{([^}]+)\}").unwrap());
+static CALL_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*(\w+)\s*(?:\((.*)\))?\s*$").unwrap());
+
+/// Evaluates generator expressions like `%{randomInt(1,100)}` in a string.
+/// Returns None if there are no expressions. A string consisting of a single
+/// expression is replaced with the generated value as is, keeping its JSON type
+pub fn eval_generators(defn: &str) -> Result<Option<Value>, Error> {
+    let captures = GENERATOR_PATTERN.captures_iter(defn).collect::<Vec<_>>();
+
+    if captures.is_empty() {
+        return Ok(None);
+    }
+
+    if let [cap] = &captures[..] {
+        if cap.get(0).map(|m| m.as_str()) == Some(defn) {
+            return eval_call(&cap[1]).map(Some);
+        }
+    }
+
+    let mut failure: Option<Error> = None;
+
+    let rendered = GENERATOR_PATTERN.replace_all(defn, |caps: &Captures| match eval_call(&caps[1]) {
+        Ok(Value::String(s)) => s,
+        Ok(other) => other.to_string(),
+        Err(e) => {
+            failure.get_or_insert(e);
+            String::new()
+        }
+    }).to_string();
+
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(Some(Value::String(rendered)))
+    }
+}
+
+fn eval_call(expr: &str) -> Result<Value, Error> {
+    let caps = CALL_PATTERN.captures(expr).ok_or_else(|| Error::new(format!("Incorrect generator expression '{}'", expr)))?;
+
+    let name = &caps[1];
+    let args = match (name, caps.get(2)) {
+        // date patterns may contain commas
+        ("now" | "today", Some(m)) => vec![m.as_str().trim().trim_matches('"').to_string()],
+        (_, Some(m)) => m.as_str().split(',').map(|arg| arg.trim().trim_matches('"').to_string()).collect::<Vec<_>>(),
+        (_, None) => vec![]
+    };
+
+    let mut rng = rand::thread_rng();
+
+    match (name, &args[..]) {
+        ("UUID", []) => Ok(Value::String(Uuid::new_v4().to_string())),
+        ("randomString", [len]) => Ok(Value::String(Alphanumeric.sample_string(&mut rng, parse_arg(len)?))),
+        ("randomString", [alphabet, min_len, max_len]) => {
+            let chars = alphabet.chars().collect::<Vec<_>>();
+            let (min_len, max_len): (usize, usize) = (parse_arg(min_len)?, parse_arg(max_len)?);
+            if chars.is_empty() || min_len >= max_len {
+                return Err(Error::new(format!("Incorrect arguments in '{}'", expr)));
+            }
+            let len = rng.gen_range(min_len..max_len);
+            Ok(Value::String((0..len).map(|_| chars[rng.gen_range(0..chars.len())]).collect()))
+        }
+        ("randomNumericString", [len]) => {
+            let len: usize = parse_arg(len)?;
+            Ok(Value::String((0..len).map(|_| char::from(b'0' + rng.gen_range(0..10u8))).collect()))
+        }
+        ("randomInt" | "randomLong", [max]) => random_between(0, parse_arg(max)?, expr),
+        ("randomInt" | "randomLong", [min, max]) => random_between(parse_arg(min)?, parse_arg(max)?, expr),
+        ("now", [format]) => Ok(Value::String(Utc::now().format(&java_to_strftime(format)).to_string())),
+        ("today", [format]) => Ok(Value::String(Local::now().date_naive().format(&java_to_strftime(format)).to_string())),
+        _ => Err(Error::new(format!("Unknown generator '{}'", expr)))
+    }
+}
+
+/// Picks a random number from `[min, max)`
+fn random_between(min: i64, max: i64, expr: &str) -> Result<Value, Error> {
+    if min >= max {
+        return Err(Error::new(format!("Empty range in '{}'", expr)));
+    }
+
+    Ok(Value::from(rand::thread_rng().gen_range(min..max)))
+}
+
+fn parse_arg<T: std::str::FromStr>(arg: &str) -> Result<T, Error> {
+    arg.parse::<T>().map_err(|_| Error::new(format!("Incorrect generator argument '{}'", arg)))
+}
+
+/// Converts a Java-style date pattern (like `yyyy-MM-dd'T'HH:mm:ss`) into a strftime one
+fn java_to_strftime(pattern: &str) -> String {
+    let chars = pattern.chars().collect::<Vec<_>>();
+    let mut res = String::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let ch = chars[pos];
+
+        if ch == '\'' {
+            let end = chars[pos + 1..].iter().position(|c| *c == '\'').map(|p| pos + 1 + p).unwrap_or(chars.len());
+            chars[pos + 1..end].iter().for_each(|c| push_literal(&mut res, *c));
+            pos = end + 1;
+            continue;
+        }
+
+        let run = chars[pos..].iter().take_while(|c| **c == ch).count();
+
+        match (ch, run) {
+            ('y', 2) => res.push_str("%y"),
+            ('y', _) => res.push_str("%Y"),
+            ('M', 1 | 2) => res.push_str("%m"),
+            ('M', 3) => res.push_str("%b"),
+            ('M', _) => res.push_str("%B"),
+            ('d', _) => res.push_str("%d"),
+            ('H', _) => res.push_str("%H"),
+            ('h', _) => res.push_str("%I"),
+            ('m', _) => res.push_str("%M"),
+            ('s', _) => res.push_str("%S"),
+            ('S', 1..=3) => res.push_str("%3f"),
+            ('S', 4..=6) => res.push_str("%6f"),
+            ('S', _) => res.push_str("%9f"),
+            ('a', _) => res.push_str("%p"),
+            ('Z', _) => res.push_str("%z"),
+            ('X', _) => res.push_str("%:z"),
+            (c, n) => (0..n).for_each(|_| push_literal(&mut res, c))
+        }
+
+        pos += run;
+    }
+
+    res
+}
+
+fn push_literal(res: &mut String, c: char) {
+    if c == '%' {
+        res.push_str("%%");
+    } else {
+        res.push(c);
+    }
+}
+
+#[cfg(test)]
+mod generators_tests {
+    use crate::utils::transformations::generators::{eval_generators, java_to_strftime};
+    use regex::Regex;
+
+    #[test]
+    fn strings_without_generators_should_be_ignored() {
+        assert!(eval_generators("plain ${req.value}").ok().unwrap().is_none());
+    }
+
+    #[test]
+    fn random_int_should_keep_number_type() {
+        let res = eval_generators("%{randomInt(1,100)}").ok().unwrap().unwrap();
+
+        assert!(res.is_i64());
+        assert!((1..100).contains(&res.as_i64().unwrap()));
+    }
+
+    #[test]
+    fn random_string_should_have_requested_length() {
+        let res = eval_generators("%{randomString(10)}").ok().unwrap().unwrap();
+        assert_eq!(res.as_str().unwrap().len(), 10);
+
+        let res = eval_generators(r#"%{randomString("AB", 2, 3)}"#).ok().unwrap().unwrap();
+        assert!(Regex::new("^[AB]{2}$").unwrap().is_match(res.as_str().unwrap()));
+
+        let res = eval_generators("%{randomNumericString(6)}").ok().unwrap().unwrap();
+        assert!(Regex::new(r"^\d{6}$").unwrap().is_match(res.as_str().unwrap()));
+    }
+
+    #[test]
+    fn uuid_should_be_generated() {
+        let res = eval_generators("%{UUID}").ok().unwrap().unwrap();
+
+        assert!(Regex::new("^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[0-9a-f]{4}-[0-9a-f]{12}$").unwrap().is_match(res.as_str().unwrap()));
+    }
+
+    #[test]
+    fn generators_should_be_interpolated_into_string() {
+        let res = eval_generators(r#"id-%{randomInt(5,6)} at %{now("yyyy")}"#).ok().unwrap().unwrap();
+
+        assert!(Regex::new(r"^id-5 at \d{4}$").unwrap().is_match(res.as_str().unwrap()));
+
+        let res = eval_generators(r#"%{now("dd MMM, yyyy")}"#).ok().unwrap().unwrap();
+        assert!(Regex::new(r"^\d{2} \w{3}, \d{4}$").unwrap().is_match(res.as_str().unwrap()));
+    }
+
+    #[test]
+    fn unknown_generators_should_be_rejected() {
+        assert!(eval_generators("%{randomBool}").is_err());
+        assert!(eval_generators("%{randomInt(10,1)}").is_err());
+        assert!(eval_generators("prefix %{randomInt(x)}").is_err());
+    }
+
+    #[test]
+    fn java_date_patterns_should_be_converted() {
+        assert_eq!(java_to_strftime("yyyy-MM-dd"), "%Y-%m-%d");
+        assert_eq!(java_to_strftime("yyyy-MM-dd'T'HH:mm:ss.SSS"), "%Y-%m-%dT%H:%M:%S.%3f");
+        assert_eq!(java_to_strftime("dd.MM.yy '100%'"), "%d.%m.%y 100%%");
+    }
+}
\ No newline at end of file
@@ -3,7 +3,9 @@ use serde_json::de;
 use serde_json::{Number, Value};
 use std::collections::HashMap;
 use std::sync::LazyLock;
+use crate::error::Error;
 use crate::utils::js::optic::{JsonOptic, ValueExt};
+use crate::utils::transformations::generators::eval_generators;
 
 static JSON_OPTIC_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\$([:~])?\{([\p{L}\d\.\[\]\-_]+)\}").unwrap());
 
@@ -37,37 +39,40 @@ impl JsonTemplater {
             return None;
         }
 
+        // a lone placeholder keeps the JSON type of the substituted value
         if let [cap] = &captures[..] {
-            let modifier = cap.get(1).map(|m| m.as_str());
-            let path = &cap[2];
-            let optic = JsonOptic::from_path(path);
+            if cap.get(0).map(|m| m.as_str()) == Some(defn) {
+                let modifier = cap.get(1).map(|m| m.as_str());
+                let path = &cap[2];
+                let optic = JsonOptic::from_path(path);
 
-            if self.values.validate(&optic) {
-                let mut new_value = self.values.get_all(&optic)[0].clone();
+                if self.values.validate(&optic) {
+                    let mut new_value = self.values.get_all(&optic)[0].clone();
 
-                if modifier == Some(":") {
-                    new_value = cast_to_string(new_value);
-                } else if modifier == Some("~") {
-                    new_value = cast_from_string(new_value);
+                    if modifier == Some(":") {
+                        new_value = cast_to_string(new_value);
+                    } else if modifier == Some("~") {
+                        new_value = cast_from_string(new_value);
+                    }
+
+                    return Some(JsonPatcher::new(new_value))
                 }
 
-                return Some(JsonPatcher::new(new_value))
+                return None;
             }
-        } else {
-            let replacement = |caps: &Captures| -> String {
-                let path = &caps[2];
-                let optic = JsonOptic::from_path(path);
+        }
 
-                let str_value = self.values.get_all(&optic).first().map(|v| render_subst(v));
-                str_value.unwrap_or(path.to_string())
-            };
+        let replacement = |caps: &Captures| -> String {
+            let path = &caps[2];
+            let optic = JsonOptic::from_path(path);
 
-            return Some(JsonPatcher::new(Value::String(
-                JSON_OPTIC_PATTERN.replace_all(defn, replacement).to_string()
-            )))
-        }
+            let str_value = self.values.get_all(&optic).first().map(|v| render_subst(v));
+            str_value.unwrap_or(path.to_string())
+        };
 
-        None
+        Some(JsonPatcher::new(Value::String(
+            JSON_OPTIC_PATTERN.replace_all(defn, replacement).to_string()
+        )))
     }
 }
 
@@ -76,6 +81,8 @@ pub trait JsonTransformations {
     fn update_in_place_by_closure(&mut self, modify: &dyn Fn(&mut Value));
     fn substitute_in_place(&mut self, values: Value);
     fn patch_in_place(&mut self, values: Value, schema: HashMap<JsonOptic, String>);
+    /// Replaces generator expressions like `%{UUID}` with generated values
+    fn eval_in_place(&mut self) -> Result<(), Error>;
 }
 
 impl JsonTransformations for Value {
@@ -99,13 +106,10 @@ impl JsonTransformations for Value {
         let templater = JsonTemplater::new(values);
 
         let upd = |vx: &mut Value| {
-            match &vx {
-                Value::String(s) => {
-                    if let Some(patcher) = templater.make_patcher_fn(&s) {
-                        patcher.apply(vx)
-                    }
-                },
-                _ => ()
+            if let Value::String(s) = &vx {
+                if let Some(patcher) = templater.make_patcher_fn(s) {
+                    patcher.apply(vx)
+                }
             }
         };
 
@@ -123,6 +127,20 @@ impl JsonTransformations for Value {
             }
         }
     }
+
+    fn eval_in_place(&mut self) -> Result<(), Error> {
+        match self {
+            Value::String(s) => {
+                if let Some(generated) = eval_generators(s)? {
+                    *self = generated;
+                }
+                Ok(())
+            }
+            Value::Array(vs) => vs.iter_mut().try_for_each(|el| el.eval_in_place()),
+            Value::Object(kvs) => kvs.iter_mut().try_for_each(|(_, val)| val.eval_in_place()),
+            _ => Ok(())
+        }
+    }
 }
 
 fn cast_to_string(value: Value) -> Value {
@@ -151,7 +169,7 @@ fn render_subst(value: &Value) -> String {
         Value::Bool(b) => b.to_string(),
         Value::Number(n) => n.to_string(),
         Value::String(s) => s.clone(),
-        Value::Array(vs) => vs.iter().map(|j| render_subst(j)).collect::<Vec<_>>().join(", "),
+        Value::Array(vs) => vs.iter().map(render_subst).collect::<Vec<_>>().join(", "),
         _ => serde_json::to_string(value).unwrap()
     }
 }
@@ -231,6 +249,25 @@ mod json_templater_tests {
         assert_eq!(template, json!({"value": {"peka": "name"}}))
     }
 
+    #[test]
+    fn single_placeholder_inside_a_string() {
+        let mut template: Value = json!(
+            {
+                "value": "/orders/${id}/items"
+            }
+        );
+
+        let data: Value = json!(
+            {
+                "id": 42
+            }
+        );
+
+        template.substitute_in_place(data);
+
+        assert_eq!(template, json!({"value": "/orders/42/items"}))
+    }
+
     #[test]
     fn convert_to_a_string() {
         let mut template: Value = json!(
@@ -289,6 +326,22 @@ mod json_templater_tests {
         ))
     }
 
+    #[test]
+    fn eval_generators_in_nested_values() {
+        let mut seed: Value = json!({
+            "id": "%{randomInt(7,8)}",
+            "codes": ["%{randomNumericString(3)}", "plain"],
+            "nested": {"flag": true}
+        });
+
+        seed.eval_in_place().ok().unwrap();
+
+        assert_eq!(seed["id"], json!(7));
+        assert_eq!(seed["codes"][0].as_str().map(|s| s.len()), Some(3));
+        assert_eq!(seed["codes"][1], json!("plain"));
+        assert_eq!(seed["nested"], json!({"flag": true}));
+    }
+
     #[test]
     fn json_patcher() {
         let mut target: Value = json!(
@@ -0,0 +1,243 @@
+use crate::error::Error;
+use crate::utils::js::optic::{JsonOptic, ValueExt};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error as DeError;
+use serde_json::Value;
+use sxd_document::dom::{ChildOfElement, ChildOfRoot, Document, Element};
+use sxd_document::parser;
+use sxd_xpath::{Context, Factory, Value as XValue, XPath};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::LazyLock;
+
+type QualifiedName = (Option<String>, String);
+
+static PLACEHOLDER_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\$\{([^}]+)\}").unwrap());
+static LITERAL_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"'[^']*'|"[^"]*""#).unwrap());
+static PREFIX_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:^|[^\w:.\-])([A-Za-z_][\w.\-]*):[A-Za-z_*]").unwrap());
+
+/// Normalized XML tree: prefixes are resolved into namespace URIs, attributes are sorted,
+/// comments, processing instructions and whitespace-only text are dropped
+#[derive(Debug, PartialEq, Eq)]
+pub enum XmlNode {
+    Element {
+        name: QualifiedName,
+        attributes: BTreeMap<QualifiedName, String>,
+        children: Vec<XmlNode>
+    },
+    Text(String)
+}
+
+pub fn canonicalize(xml: &str) -> Result<XmlNode, Error> {
+    let package = parser::parse(xml).map_err(|e| Error::new(format!("Incorrect XML: {}", e)))?;
+    let document = package.as_document();
+
+    document.root().children().into_iter()
+        .find_map(|child| match child {
+            ChildOfRoot::Element(el) => Some(canonicalize_element(el)),
+            _ => None
+        })
+        .ok_or_else(|| Error::new("XML document has no root element".to_string()))
+}
+
+fn canonicalize_element(element: Element<'_>) -> XmlNode {
+    let name = (element.name().namespace_uri().map(str::to_string), element.name().local_part().to_string());
+
+    let attributes = element.attributes().into_iter()
+        .map(|attr| ((attr.name().namespace_uri().map(str::to_string), attr.name().local_part().to_string()), attr.value().to_string()))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut children = vec![];
+    let mut text = String::new();
+
+    for child in element.children() {
+        match child {
+            ChildOfElement::Element(el) => {
+                push_text(&mut children, &mut text);
+                children.push(canonicalize_element(el));
+            }
+            // adjacent text and CDATA sections form a single text node
+            ChildOfElement::Text(t) => text.push_str(t.text()),
+            _ => ()
+        }
+    }
+    push_text(&mut children, &mut text);
+
+    XmlNode::Element { name, attributes, children }
+}
+
+fn push_text(children: &mut Vec<XmlNode>, text: &mut String) {
+    let trimmed = text.trim();
+
+    if !trimmed.is_empty() {
+        children.push(XmlNode::Text(trimmed.to_string()));
+    }
+
+    text.clear();
+}
+
+/// XML document along with its canonical form, serialized as the original text
+#[derive(Debug)]
+pub struct XmlDocument {
+    pub text: String,
+    pub canonical: XmlNode
+}
+
+impl Serialize for XmlDocument {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.text.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for XmlDocument {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let text = String::deserialize(deserializer)?;
+        let canonical = canonicalize(&text).map_err(|e| D::Error::custom(e.cause))?;
+
+        Ok(XmlDocument { text, canonical })
+    }
+}
+
+/// XPath expression built once, along with the namespace prefixes it uses
+pub struct CompiledXPath {
+    xpath: XPath,
+    prefixes: Vec<String>
+}
+
+// SAFETY: `sxd_xpath` doesn't mark expressions as `Send` and `Sync`, though they are immutable trees of owned values
+// without interior mutability, and evaluation only reads them
+unsafe impl Send for CompiledXPath {}
+unsafe impl Sync for CompiledXPath {}
+
+impl CompiledXPath {
+    /// Returns None if the expression is incorrect
+    pub fn compile(xpath: &str) -> Option<CompiledXPath> {
+        let built = Factory::new().build(xpath).ok().flatten()?;
+
+        Some(CompiledXPath { xpath: built, prefixes: prefixes(xpath) })
+    }
+
+    /// Evaluates the expression against the document. Returns None if it uses a prefix missing in `namespaces`
+    pub fn evaluate<'d>(&self, document: &Document<'d>, namespaces: &HashMap<String, String>) -> Option<XValue<'d>> {
+        // evaluation panics on prefixes missing in the context
+        if !self.prefixes.iter().all(|prefix| namespaces.contains_key(prefix)) {
+            return None;
+        }
+
+        let mut context = Context::new();
+        namespaces.iter().for_each(|(prefix, uri)| context.set_namespace(prefix, uri));
+
+        self.xpath.evaluate(&context, document.root()).ok()
+    }
+}
+
+/// Evaluates XPath expression against the document. Returns None if the expression is incorrect
+/// or uses a prefix missing in `namespaces`
+pub fn evaluate_xpath<'d>(document: &Document<'d>, xpath: &str, namespaces: &HashMap<String, String>) -> Option<XValue<'d>> {
+    CompiledXPath::compile(xpath)?.evaluate(document, namespaces)
+}
+
+/// Namespace prefixes of the names used in XPath expression
+fn prefixes(xpath: &str) -> Vec<String> {
+    let without_literals = LITERAL_PATTERN.replace_all(xpath, "");
+
+    PREFIX_PATTERN.captures_iter(&without_literals).map(|caps| caps[1].to_string()).collect()
+}
+
+/// Substitutes `${...}` placeholders in XML template. Placeholders starting with `/` are XPath expressions
+/// evaluated against the `source` document, other ones are paths in the `context`.
+/// Substituted values are escaped, unresolved placeholders are left as is
+pub fn render_xml(template: &str, context: &Value, source: Option<&Document<'_>>, namespaces: &HashMap<String, String>) -> String {
+    PLACEHOLDER_PATTERN.replace_all(template, |caps: &Captures| {
+        let path = caps[1].trim();
+
+        let value = if path.starts_with('/') {
+            source.and_then(|doc| evaluate_xpath(doc, path, namespaces)).map(|v| v.string())
+        } else {
+            context.get_all(&JsonOptic::from_path(path)).first().map(|v| match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string()
+            })
+        };
+
+        value.map(|v| escape(&v)).unwrap_or_else(|| caps[0].to_string())
+    }).to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Deserializes a string which should be a well-formed XML document
+pub fn deserialize_xml<'de, D>(deserializer: D) -> Result<String, D::Error> where D: Deserializer<'de> {
+    let xml = String::deserialize(deserializer)?;
+
+    canonicalize(&xml).map_err(|e| D::Error::custom(e.cause))?;
+
+    Ok(xml)
+}
+
+#[cfg(test)]
+mod xml_tests {
+    use crate::utils::xml::{canonicalize, evaluate_xpath, render_xml};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use sxd_document::parser;
+
+    #[test]
+    fn formatting_and_prefixes_should_not_matter() {
+        let etalon = canonicalize(r#"<s:Envelope xmlns:s="urn:soap"><s:Body><user id="1" role="admin">peka</user></s:Body></s:Envelope>"#).unwrap();
+
+        let formatted = canonicalize(r#"<?xml version="1.0"?>
+            <env:Envelope xmlns:env="urn:soap">
+                <!-- request -->
+                <env:Body>
+                    <user role="admin" id="1"><![CDATA[peka]]></user>
+                </env:Body>
+            </env:Envelope>"#).unwrap();
+
+        assert_eq!(etalon, formatted);
+    }
+
+    #[test]
+    fn different_documents_should_not_be_equal() {
+        let etalon = canonicalize(r#"<user id="1">peka</user>"#).unwrap();
+
+        assert_ne!(etalon, canonicalize(r#"<user id="2">peka</user>"#).unwrap());
+        assert_ne!(etalon, canonicalize(r#"<user id="1">name</user>"#).unwrap());
+        assert_ne!(etalon, canonicalize(r#"<user xmlns="urn:other" id="1">peka</user>"#).unwrap());
+    }
+
+    #[test]
+    fn template_should_be_rendered_from_context_and_source_document() {
+        let package = parser::parse(r#"<s:Envelope xmlns:s="urn:soap"><s:Body><user id="7">peka</user></s:Body></s:Envelope>"#).unwrap();
+        let namespaces = HashMap::from([("soap".to_string(), "urn:soap".to_string())]);
+
+        let res = render_xml(
+            r#"<reply id="${/soap:Envelope/soap:Body/user/@id}" visits="${state.visits}"><name>${//user}</name><note>${query.note}</note><missing>${req.none}</missing></reply>"#,
+            &json!({"state": {"visits": 3}, "query": {"note": "<b> & co"}}),
+            Some(&package.as_document()),
+            &namespaces
+        );
+
+        assert_eq!(res, r#"<reply id="7" visits="3"><name>peka</name><note>&lt;b&gt; &amp; co</note><missing>${req.none}</missing></reply>"#);
+    }
+
+    #[test]
+    fn undeclared_prefixes_should_not_be_evaluated() {
+        let package = parser::parse(r#"<s:Envelope xmlns:s="urn:soap"/>"#).unwrap();
+
+        assert!(evaluate_xpath(&package.as_document(), "/soap:Envelope", &HashMap::new()).is_none());
+        assert!(evaluate_xpath(&package.as_document(), "count(/*[local-name()='a:b'])", &HashMap::new()).is_some());
+    }
+
+    #[test]
+    fn malformed_xml_should_be_rejected() {
+        assert!(canonicalize("<user>").is_err());
+        assert!(canonicalize("{\"user\": 1}").is_err());
+    }
+}
\ No newline at end of file